@@ -1,5 +1,9 @@
+use crate::error::{CastleError, ChessError};
 use crate::piece::{Move, MoveShape, Offset, Piece, PieceColor, PieceType, ShapeData};
-use std::ops::Add;
+use crate::zobrist;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Add, Index, Sub};
 
 #[derive(Debug, PartialEq)]
 pub enum MoveResult {
@@ -8,15 +12,66 @@ pub enum MoveResult {
     Illegal,
 }
 
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct MoveInfo {
+    pub move_: Move,
+    pub piece: Piece,
+    pub captured: Option<Piece>,
+    pub is_castling: bool,
+    pub is_en_passant: bool,
+}
+
+// Counts over the legal moves of the side to move, each promotion piece counts as its own move
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct MoveStats {
+    pub total: usize,
+    pub captures: usize,
+    pub checks: usize,
+    pub promotions: usize,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum GameStatus {
+    Ongoing,
+    Checkmate,
+    Stalemate,
+    InsufficientMaterial,
+    // Draws that apply without either player claiming them
+    FivefoldRepetition,
+    SeventyFiveMove,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DrawReason {
+    Stalemate,
+    FiftyMove,
+    SeventyFiveMove,
+    ThreefoldRepetition,
+    FivefoldRepetition,
+    InsufficientMaterial,
+}
+
 pub const BOARD_WIDTH: i8 = 8;
 pub const BOARD_HEIGHT: i8 = 8;
+// Length of Board::to_bytes
+pub const BOARD_BYTES: usize = 44;
 
-#[derive(Clone, Copy)]
+// Move number of the position after the given number of plies from the start
+pub fn fullmove_from_ply(ply: u32) -> u32 {
+    ply / 2 + 1
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct CastlingRights {
     white_kingside: bool,
     white_queenside: bool,
     black_kingside: bool,
     black_queenside: bool,
+    // Starting files of the castling rooks, only differs from a/h in Chess960
+    white_kingside_rook: i8,
+    white_queenside_rook: i8,
+    black_kingside_rook: i8,
+    black_queenside_rook: i8,
 }
 
 impl CastlingRights {
@@ -26,6 +81,26 @@ impl CastlingRights {
             white_queenside: true,
             black_kingside: true,
             black_queenside: true,
+            white_kingside_rook: 7,
+            white_queenside_rook: 0,
+            black_kingside_rook: 7,
+            black_queenside_rook: 0,
+        }
+    }
+
+    // Castling rights with the rooks on their standard a/h files
+    pub fn from_flags(
+        white_kingside: bool,
+        white_queenside: bool,
+        black_kingside: bool,
+        black_queenside: bool,
+    ) -> Self {
+        Self {
+            white_kingside,
+            white_queenside,
+            black_kingside,
+            black_queenside,
+            ..Self::new()
         }
     }
 
@@ -38,6 +113,75 @@ impl CastlingRights {
         }
     }
 
+    fn rook_file(&self, color: PieceColor, kingside: bool) -> i8 {
+        match (color, kingside) {
+            (PieceColor::White, true) => self.white_kingside_rook,
+            (PieceColor::White, false) => self.white_queenside_rook,
+            (PieceColor::Black, true) => self.black_kingside_rook,
+            (PieceColor::Black, false) => self.black_queenside_rook,
+        }
+    }
+
+    fn allow_castling(&mut self, color: PieceColor, kingside: bool, rook_file: i8) {
+        match (color, kingside) {
+            (PieceColor::White, true) => {
+                self.white_kingside = true;
+                self.white_kingside_rook = rook_file;
+            }
+            (PieceColor::White, false) => {
+                self.white_queenside = true;
+                self.white_queenside_rook = rook_file;
+            }
+            (PieceColor::Black, true) => {
+                self.black_kingside = true;
+                self.black_kingside_rook = rook_file;
+            }
+            (PieceColor::Black, false) => {
+                self.black_queenside = true;
+                self.black_queenside_rook = rook_file;
+            }
+        }
+    }
+
+    // Rooks on other files than a/h are written as Shredder-FEN file letters
+    pub fn to_fen(&self) -> String {
+        let mut fen = String::new();
+        for color in [PieceColor::White, PieceColor::Black] {
+            for kingside in [true, false] {
+                if !self.can_castle(color, kingside) {
+                    continue;
+                }
+                let rook_file = self.rook_file(color, kingside);
+                let ch = match (kingside, rook_file) {
+                    (true, 7) => 'K',
+                    (false, 0) => 'Q',
+                    _ => (b'A' + rook_file as u8) as char,
+                };
+                fen.push(match color {
+                    PieceColor::White => ch,
+                    PieceColor::Black => ch.to_ascii_lowercase(),
+                });
+            }
+        }
+        if fen.is_empty() {
+            fen.push('-');
+        }
+        fen
+    }
+
+    fn flipped(&self) -> Self {
+        Self {
+            white_kingside: self.black_kingside,
+            white_queenside: self.black_queenside,
+            black_kingside: self.white_kingside,
+            black_queenside: self.white_queenside,
+            white_kingside_rook: self.black_kingside_rook,
+            white_queenside_rook: self.black_queenside_rook,
+            black_kingside_rook: self.white_kingside_rook,
+            black_queenside_rook: self.white_queenside_rook,
+        }
+    }
+
     fn disable_king_castling(&mut self, color: PieceColor) {
         match color {
             PieceColor::White => {
@@ -61,7 +205,7 @@ impl CastlingRights {
     }
 }
 
-#[derive(Copy, Clone, PartialEq, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub struct Position {
     pub file: i8,
     pub rank: i8,
@@ -79,7 +223,92 @@ impl Position {
         (self.file < BOARD_WIDTH) && (self.rank < BOARD_HEIGHT)
     }
 
-    fn to_index(&self) -> Result<usize, String> {
+    pub fn knight_targets(&self) -> Vec<Position> {
+        Offset::KNIGHT_JUMPS
+            .into_iter()
+            .map(|offset| *self + offset)
+            .filter(|target| target.is_on_board())
+            .collect()
+    }
+
+    // The on-board squares a king could step to from here
+    pub fn neighbors(&self) -> impl Iterator<Item = Position> {
+        let pos = *self;
+        Offset::DIRECTIONS
+            .into_iter()
+            .map(move |offset| pos + offset)
+            .filter(|neighbor| neighbor.is_on_board())
+    }
+
+    // The offset that takes this square to other
+    pub fn offset_to(&self, other: Position) -> Offset {
+        other - *self
+    }
+
+    // Number of king moves between the squares
+    pub fn chebyshev_distance(&self, other: Position) -> i8 {
+        let offset = self.offset_to(other);
+        offset.file.abs().max(offset.rank.abs())
+    }
+
+    pub fn manhattan_distance(&self, other: Position) -> i8 {
+        let offset = self.offset_to(other);
+        offset.file.abs() + offset.rank.abs()
+    }
+
+    // a1 is a dark square, so squares with an even file + rank are dark
+    pub fn is_light(&self) -> bool {
+        (self.file + self.rank) % 2 == 1
+    }
+
+    pub fn is_dark(&self) -> bool {
+        !self.is_light()
+    }
+
+    // The file must be on the board, other files give a meaningless character
+    pub fn file_char(&self) -> char {
+        debug_assert!(
+            (0..BOARD_WIDTH).contains(&self.file),
+            "File is off the board"
+        );
+        (b'a'.wrapping_add(self.file as u8)) as char
+    }
+
+    // The rank must be on the board, other ranks give a meaningless character
+    pub fn rank_char(&self) -> char {
+        debug_assert!(
+            (0..BOARD_HEIGHT).contains(&self.rank),
+            "Rank is off the board"
+        );
+        (b'1'.wrapping_add(self.rank as u8)) as char
+    }
+
+    // Squares off the board are written as ??
+    pub fn to_algebraic(&self) -> String {
+        if self.is_on_board() {
+            format!("{}{}", self.file_char(), self.rank_char())
+        } else {
+            "??".to_string()
+        }
+    }
+
+    pub fn from_algebraic(square: &str) -> Result<Self, String> {
+        let mut chars = square.chars();
+        let (Some(file_char), Some(rank_char), None) = (chars.next(), chars.next(), chars.next())
+        else {
+            return Err(format!("Invalid square: {}", square));
+        };
+        if !file_char.is_ascii() || !rank_char.is_ascii() {
+            return Err(format!("Invalid square: {}", square));
+        }
+        let pos = Position::new((file_char as i8) - 'a' as i8, (rank_char as i8) - '1' as i8);
+        if !pos.is_on_board() {
+            return Err(format!("Square out of bounds: {}", square));
+        }
+        Ok(pos)
+    }
+
+    fn to_index(self) -> Result<usize, String> {
         if !self.is_on_board() {
             return Err("Position is not on board".to_string());
         }
@@ -92,6 +321,10 @@ impl Position {
         let file = (index as i8) % BOARD_WIDTH;
         Position::new(file, rank)
     }
+
+    fn flipped(&self) -> Self {
+        Position::new(self.file, BOARD_HEIGHT - 1 - self.rank)
+    }
 }
 
 impl Add<Offset> for Position {
@@ -101,25 +334,154 @@ impl Add<Offset> for Position {
     }
 }
 
+impl Sub for Position {
+    type Output = Offset;
+    fn sub(self, other: Position) -> Self::Output {
+        Offset::new(self.file - other.file, self.rank - other.rank)
+    }
+}
+
+// Uppercase for white, lowercase for black, as in FEN
+fn fen_char(piece: Piece) -> char {
+    let ch = match piece.type_ {
+        PieceType::Pawn => 'p',
+        PieceType::Knight => 'n',
+        PieceType::Bishop => 'b',
+        PieceType::Rook => 'r',
+        PieceType::Queen => 'q',
+        PieceType::King => 'k',
+    };
+    match piece.color {
+        PieceColor::White => ch.to_ascii_uppercase(),
+        PieceColor::Black => ch,
+    }
+}
+
+// Four bits per square for to_bytes: the piece type in the low three bits, black sets the high bit
+fn piece_code(piece: Option<Piece>) -> u8 {
+    let Some(piece) = piece else {
+        return 0;
+    };
+    let type_code = match piece.type_ {
+        PieceType::Pawn => 1,
+        PieceType::Knight => 2,
+        PieceType::Bishop => 3,
+        PieceType::Rook => 4,
+        PieceType::Queen => 5,
+        PieceType::King => 6,
+    };
+    match piece.color {
+        PieceColor::White => type_code,
+        PieceColor::Black => type_code | 8,
+    }
+}
+
+fn piece_from_code(code: u8) -> Result<Option<Piece>, ChessError> {
+    let type_ = match code & 7 {
+        0 if code == 0 => return Ok(None),
+        1 => PieceType::Pawn,
+        2 => PieceType::Knight,
+        3 => PieceType::Bishop,
+        4 => PieceType::Rook,
+        5 => PieceType::Queen,
+        6 => PieceType::King,
+        _ => {
+            return Err(ChessError::InvalidPosition(format!(
+                "Invalid piece code {}",
+                code
+            )));
+        }
+    };
+    let color = if code & 8 == 0 {
+        PieceColor::White
+    } else {
+        PieceColor::Black
+    };
+    Ok(Some(Piece { type_, color }))
+}
+
 #[derive(Clone, Copy)]
 pub enum MoveTurn {
     White,
     Black,
 }
 
-#[derive(Clone, Copy, Debug)]
-enum CastlingSide {
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CastlingSide {
     Kingside,
     Queenside,
 }
 
+impl CastlingSide {
+    // The king and rook always end up on the standard squares, also in Chess960:
+    // g and f when castling kingside, c and d when castling queenside
+    pub fn king_target(self, color: PieceColor) -> Position {
+        let file = match self {
+            CastlingSide::Kingside => 6,
+            CastlingSide::Queenside => 2,
+        };
+        Position::new(file, back_rank(color))
+    }
+
+    pub fn rook_target(self, color: PieceColor) -> Position {
+        let file = match self {
+            CastlingSide::Kingside => 5,
+            CastlingSide::Queenside => 3,
+        };
+        Position::new(file, back_rank(color))
+    }
+}
+
+fn back_rank(color: PieceColor) -> i8 {
+    match color {
+        PieceColor::White => 0,
+        PieceColor::Black => BOARD_HEIGHT - 1,
+    }
+}
+
+// What a played move changed, so unmake_move can put it back
+#[derive(Clone, Copy)]
+struct Undo {
+    move_: Move,
+    piece: Piece,
+    // The captured piece and the square it was taken on, which differs for en passant
+    captured: Option<(Position, Piece)>,
+    castling: Option<CastlingSide>,
+    castling_rights: CastlingRights,
+    en_passant_target: Option<Position>,
+    halfmove_clock: u32,
+    fullmove_number: u32,
+}
+
 #[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "crate::json::BoardJson", try_from = "crate::json::BoardJson")
+)]
 pub struct Board {
     pieces: [Option<Piece>; (BOARD_WIDTH * BOARD_HEIGHT) as usize],
     move_turn: MoveTurn,
     castling_rights: CastlingRights,
     en_passant_target: Option<Position>,
     promotion_move: Option<Move>,
+    chess960: bool,
+    // Kept in sync by moves, so finding a king doesn't need a scan. Only used when kings_cached
+    // is set, which needs at most one king per side.
+    white_king: Option<Position>,
+    black_king: Option<Position>,
+    kings_cached: bool,
+    // Plies since the last capture or pawn move, and the move number starting at 1
+    halfmove_clock: u32,
+    fullmove_number: u32,
+    // Piece part of the Zobrist hash, kept up to date by set
+    pieces_hash: u64,
+    // Hashes of the positions before each move played on this board
+    hash_history: Vec<u64>,
+    // En passant targets cleared by null moves, restored when they are unmade
+    null_move_en_passant: Vec<Option<Position>>,
+    // One entry per move played on this board, parallel to hash_history
+    undo_history: Vec<Undo>,
 }
 
 impl Board {
@@ -129,13 +491,31 @@ impl Board {
         castling_rights: CastlingRights,
         en_passant_target: Option<Position>,
     ) -> Self {
-        Self {
+        let mut board = Self {
             pieces,
             move_turn,
             castling_rights,
             en_passant_target,
             promotion_move: None,
-        }
+            chess960: false,
+            white_king: None,
+            black_king: None,
+            kings_cached: false,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            pieces_hash: 0,
+            hash_history: Vec::new(),
+            null_move_en_passant: Vec::new(),
+            undo_history: Vec::new(),
+        };
+        board.cache_kings();
+        board.pieces_hash = board
+            .iter_pieces()
+            .map(|(pos, piece)| {
+                zobrist::piece_key(piece, pos.to_index().expect("Square is on board"))
+            })
+            .fold(0, |hash, key| hash ^ key);
+        board
     }
 
     pub fn starting_position() -> Self {
@@ -151,7 +531,355 @@ impl Board {
         )
     }
 
+    // Indexed as pieces[rank][file], rank 0 is white's first rank. No side may castle.
+    pub fn from_array(pieces: [[Option<Piece>; 8]; 8]) -> Self {
+        let mut board = Self::empty();
+        board.castling_rights = CastlingRights::from_flags(false, false, false, false);
+        for (rank, row) in pieces.iter().enumerate() {
+            for (file, piece) in row.iter().enumerate() {
+                board
+                    .set(Position::new(file as i8, rank as i8), *piece)
+                    .unwrap();
+            }
+        }
+        board.cache_kings();
+        board
+    }
+
+    pub fn to_array(&self) -> [[Option<Piece>; 8]; 8] {
+        let mut pieces = [[None; 8]; 8];
+        for (pos, piece) in self.iter_pieces() {
+            pieces[pos.rank as usize][pos.file as usize] = Some(piece);
+        }
+        pieces
+    }
+
+    // Fields can be separated by any whitespace, and whitespace around the FEN is ignored
     pub fn from_fen(fen: &str) -> Result<Self, String> {
+        Self::parse_fen(fen, false)
+    }
+
+    // Text diagram with the given side at the bottom, files and ranks are labelled
+    pub fn to_string_from(&self, perspective: PieceColor) -> String {
+        let (ranks, files): (Vec<i8>, Vec<i8>) = match perspective {
+            PieceColor::White => (
+                (0..BOARD_HEIGHT).rev().collect(),
+                (0..BOARD_WIDTH).collect(),
+            ),
+            PieceColor::Black => (
+                (0..BOARD_HEIGHT).collect(),
+                (0..BOARD_WIDTH).rev().collect(),
+            ),
+        };
+
+        let mut diagram = String::new();
+        for &rank in &ranks {
+            diagram.push(Position::new(0, rank).rank_char());
+            for &file in &files {
+                let ch = self
+                    .piece_at_pos(Position::new(file, rank))
+                    .map_or('.', fen_char);
+                diagram.push(' ');
+                diagram.push(ch);
+            }
+            diagram.push('\n');
+        }
+        diagram.push(' ');
+        for &file in &files {
+            diagram.push(' ');
+            diagram.push(Position::new(file, 0).file_char());
+        }
+        diagram
+    }
+
+    // The en passant square is only written when a pawn can legally capture there
+    pub fn to_fen(&self) -> String {
+        let mut placement = String::new();
+        for rank in (0..BOARD_HEIGHT).rev() {
+            let mut empty_squares = 0;
+            for file in 0..BOARD_WIDTH {
+                let Some(piece) = self.piece_at_pos(Position::new(file, rank)) else {
+                    empty_squares += 1;
+                    continue;
+                };
+                if empty_squares > 0 {
+                    placement.push_str(&empty_squares.to_string());
+                    empty_squares = 0;
+                }
+                placement.push(fen_char(piece));
+            }
+            if empty_squares > 0 {
+                placement.push_str(&empty_squares.to_string());
+            }
+            if rank > 0 {
+                placement.push('/');
+            }
+        }
+
+        let active_color = match self.move_turn {
+            MoveTurn::White => "w",
+            MoveTurn::Black => "b",
+        };
+
+        let en_passant = match self.en_passant_target {
+            Some(target)
+                if self.en_passant_target_valid()
+                    && self
+                        .legal_moves_iter()
+                        .any(|move_| move_.to() == target && self.is_move_en_passant(move_)) =>
+            {
+                target.to_algebraic()
+            }
+            _ => "-".to_string(),
+        };
+
+        format!(
+            "{} {} {} {} {} {}",
+            placement,
+            active_color,
+            self.castling_rights.to_fen(),
+            en_passant,
+            self.halfmove_clock,
+            self.fullmove_number
+        )
+    }
+
+    // Also accepts FENs missing the halfmove and fullmove fields, defaulting them to 0 and 1.
+    // Some tools write a fullmove number of 0, which is read as 1 since the move number starts
+    // there.
+    pub fn from_fen_lenient(fen: &str) -> Result<Self, String> {
+        let mut parts: Vec<&str> = fen.split_whitespace().collect();
+        if parts.len() == 4 {
+            parts.push("0");
+        }
+        if parts.len() == 5 || parts.get(5) == Some(&"0") {
+            parts.truncate(5);
+            parts.push("1");
+        }
+        Self::from_fen(&parts.join(" "))
+    }
+
+    pub fn from_fen_strict(fen: &str) -> Result<Self, String> {
+        let board = Self::from_fen(fen)?;
+        board.validate().map_err(|error| error.to_string())?;
+        Ok(board)
+    }
+
+    // Fixed size binary form: 32 bytes of piece codes, two squares per byte with a1 in the low
+    // bits of the first, then flags, castling rook files, the en passant square (255 for none)
+    // and both clocks as little endian u32
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(BOARD_BYTES);
+        for squares in self.pieces.chunks(2) {
+            bytes.push(piece_code(squares[0]) | piece_code(squares[1]) << 4);
+        }
+
+        let rights = &self.castling_rights;
+        let flags = [
+            matches!(self.move_turn, MoveTurn::Black),
+            self.chess960,
+            rights.white_kingside,
+            rights.white_queenside,
+            rights.black_kingside,
+            rights.black_queenside,
+        ];
+        let flags_byte = flags
+            .into_iter()
+            .enumerate()
+            .fold(0, |byte, (bit, set)| byte | (set as u8) << bit);
+        bytes.push(flags_byte);
+        bytes.push(rights.white_kingside_rook as u8 | (rights.white_queenside_rook as u8) << 4);
+        bytes.push(rights.black_kingside_rook as u8 | (rights.black_queenside_rook as u8) << 4);
+
+        let en_passant = self
+            .en_passant_target
+            .and_then(|target| target.to_index().ok())
+            .map_or(u8::MAX, |index| index as u8);
+        bytes.push(en_passant);
+        bytes.extend_from_slice(&self.halfmove_clock.to_le_bytes());
+        bytes.extend_from_slice(&self.fullmove_number.to_le_bytes());
+        bytes
+    }
+
+    // Reads the form written by to_bytes. Like from_fen, the position itself isn't validated.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ChessError> {
+        let invalid = |reason: &str| ChessError::InvalidPosition(reason.to_string());
+        if bytes.len() != BOARD_BYTES {
+            return Err(invalid("Board bytes have the wrong length"));
+        }
+
+        let mut pieces = [const { None }; (BOARD_WIDTH * BOARD_HEIGHT) as usize];
+        for (index, piece) in pieces.iter_mut().enumerate() {
+            *piece = piece_from_code((bytes[index / 2] >> (4 * (index % 2))) & 0xF)?;
+        }
+
+        let flags = bytes[32];
+        if flags >> 6 != 0 {
+            return Err(invalid("Unknown flags in board bytes"));
+        }
+        let flag = |bit: u8| flags & (1 << bit) != 0;
+        let rook_file = |byte: u8, shift: u8| -> Result<i8, ChessError> {
+            let file = ((byte >> shift) & 0xF) as i8;
+            if file < BOARD_WIDTH {
+                Ok(file)
+            } else {
+                Err(invalid("Castling rook file out of bounds"))
+            }
+        };
+        let castling_rights = CastlingRights {
+            white_kingside: flag(2),
+            white_queenside: flag(3),
+            black_kingside: flag(4),
+            black_queenside: flag(5),
+            white_kingside_rook: rook_file(bytes[33], 0)?,
+            white_queenside_rook: rook_file(bytes[33], 4)?,
+            black_kingside_rook: rook_file(bytes[34], 0)?,
+            black_queenside_rook: rook_file(bytes[34], 4)?,
+        };
+        let move_turn = if flag(0) {
+            MoveTurn::Black
+        } else {
+            MoveTurn::White
+        };
+        let en_passant_target = match bytes[35] {
+            u8::MAX => None,
+            index if (index as usize) < pieces.len() => Some(Position::from_index(index as usize)),
+            _ => return Err(invalid("En passant square out of bounds")),
+        };
+
+        let clock = |start: usize| {
+            u32::from_le_bytes(bytes[start..start + 4].try_into().expect("Four bytes"))
+        };
+        let mut board = Board::new(pieces, move_turn, castling_rights, en_passant_target);
+        board.chess960 = flag(1);
+        board.halfmove_clock = clock(36);
+        board.fullmove_number = match clock(40) {
+            0 => return Err(invalid("Fullmove number can't be 0")),
+            fullmove_number => fullmove_number,
+        };
+        Ok(board)
+    }
+
+    // EPD is the first four FEN fields followed by operations like `bm Nf3; id "test";`
+    pub fn from_epd(epd: &str) -> Result<(Self, HashMap<String, String>), ChessError> {
+        // The operations are kept as written after the fourth field, so whitespace inside
+        // quoted operands survives
+        let mut rest = epd;
+        let mut position_fields = Vec::new();
+        for _ in 0..4 {
+            rest = rest.trim_start();
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            if end == 0 {
+                return Err(ChessError::InvalidPosition(
+                    "EPD must start with 4 position fields".to_string(),
+                ));
+            }
+            position_fields.push(&rest[..end]);
+            rest = &rest[end..];
+        }
+        let fen = format!("{} 0 1", position_fields.join(" "));
+        let board = Self::parse_fen(&fen, false).map_err(ChessError::InvalidPosition)?;
+
+        // Operations end at a semicolon outside of quotes
+        let mut operations = HashMap::new();
+        let mut in_quotes = false;
+        let mut start = 0;
+        for (index, ch) in rest.char_indices().chain([(rest.len(), ';')]) {
+            match ch {
+                '"' => in_quotes = !in_quotes,
+                ';' if !in_quotes || index == rest.len() => {
+                    let operation = rest[start..index].trim();
+                    start = index + 1;
+                    if operation.is_empty() {
+                        continue;
+                    }
+                    let (opcode, operand) = operation
+                        .split_once(char::is_whitespace)
+                        .unwrap_or((operation, ""));
+                    let operand = operand.trim();
+                    let operand = operand
+                        .strip_prefix('"')
+                        .and_then(|operand| operand.strip_suffix('"'))
+                        .unwrap_or(operand);
+                    operations.insert(opcode.to_string(), operand.to_string());
+                }
+                _ => {}
+            }
+        }
+        Ok((board, operations))
+    }
+
+    pub fn validate(&self) -> Result<(), ChessError> {
+        let invalid = |reason: &str| Err(ChessError::InvalidPosition(reason.to_string()));
+
+        for color in [PieceColor::White, PieceColor::Black] {
+            let king_count = self
+                .iter_pieces()
+                .filter(|(_, piece)| piece.color == color && piece.type_ == PieceType::King)
+                .count();
+            if king_count != 1 {
+                return invalid("Each side must have exactly one king");
+            }
+        }
+
+        if self.iter_pieces().any(|(pos, piece)| {
+            piece.type_ == PieceType::Pawn && (pos.rank == 0 || pos.rank == BOARD_HEIGHT - 1)
+        }) {
+            return invalid("Pawns can't stand on the first or last rank");
+        }
+
+        let waiting_color = match self.move_turn {
+            MoveTurn::White => PieceColor::Black,
+            MoveTurn::Black => PieceColor::White,
+        };
+        if self.is_color_in_check(waiting_color) {
+            return invalid("The side not to move is in check");
+        }
+
+        for color in [PieceColor::White, PieceColor::Black] {
+            let back_rank = match color {
+                PieceColor::White => 0,
+                PieceColor::Black => BOARD_HEIGHT - 1,
+            };
+            for kingside in [true, false] {
+                if !self.castling_rights.can_castle(color, kingside) {
+                    continue;
+                }
+                let king_pos = self.find_king(color);
+                let king_in_place = match king_pos {
+                    Some(pos) if self.chess960 => pos.rank == back_rank,
+                    Some(pos) => pos == Position::new(4, back_rank),
+                    None => false,
+                };
+                let rook_pos =
+                    Position::new(self.castling_rights.rook_file(color, kingside), back_rank);
+                let rook_in_place = self.piece_at_pos(rook_pos)
+                    == Some(Piece {
+                        type_: PieceType::Rook,
+                        color,
+                    });
+                if !king_in_place || !rook_in_place {
+                    return invalid("Castling rights don't match the king and rook placement");
+                }
+            }
+        }
+
+        if !self.en_passant_target_valid() {
+            return invalid("En passant square doesn't match the pawn positions");
+        }
+
+        Ok(())
+    }
+
+    pub fn new_chess960(start_fen: &str) -> Result<Self, String> {
+        Self::parse_fen(start_fen, true)
+    }
+
+    pub fn is_chess960(&self) -> bool {
+        self.chess960
+    }
+
+    fn parse_fen(fen: &str, chess960: bool) -> Result<Self, String> {
         let parts: Vec<&str> = fen.split_whitespace().collect();
         if parts.len() != 6 {
             return Err("FEN string must have 6 parts".to_string());
@@ -228,41 +956,229 @@ impl Board {
         };
 
         // Parse castling rights
-        let castling_rights = CastlingRights {
-            white_kingside: castling_rights_str.contains('K'),
-            white_queenside: castling_rights_str.contains('Q'),
-            black_kingside: castling_rights_str.contains('k'),
-            black_queenside: castling_rights_str.contains('q'),
-        };
+        let castling_rights = Self::parse_castling_rights(castling_rights_str, &pieces, chess960)?;
 
         // Parse en passant target square
         let en_passant_target = match en_passant_square {
             "-" => None,
-            square => {
-                if square.len() != 2 {
-                    return Err("Invalid en passant square".to_string());
-                }
-                let file_char = square.chars().nth(0).unwrap();
-                let rank_char = square.chars().nth(1).unwrap();
+            square => Some(
+                Position::from_algebraic(square)
+                    .map_err(|err| format!("Invalid en passant square: {}", err))?,
+            ),
+        };
 
-                let file = (file_char as i8) - 'a' as i8;
-                let rank = (rank_char as i8) - '1' as i8;
+        // Both clocks must be numbers, and the move number starts at 1
+        let halfmove_clock = parts[4]
+            .parse::<u32>()
+            .map_err(|_| "Invalid halfmove clock".to_string())?;
+        let fullmove_number = match parts[5].parse::<u32>() {
+            Ok(0) => return Err("Fullmove number can't be 0".to_string()),
+            Ok(fullmove_number) => fullmove_number,
+            Err(_) => return Err("Invalid fullmove number".to_string()),
+        };
 
-                let pos = Position::new(file, rank);
-                if pos.is_on_board() {
-                    Some(pos)
-                } else {
-                    return Err("En passant square out of bounds".to_string());
+        let mut board = Board::new(pieces, move_turn, castling_rights, en_passant_target);
+        board.chess960 = chess960;
+        board.halfmove_clock = halfmove_clock;
+        board.fullmove_number = fullmove_number;
+        Ok(board)
+    }
+
+    // Accepts standard KQkq, and Shredder-FEN file letters such as HAha
+    fn parse_castling_rights(
+        castling_rights_str: &str,
+        pieces: &[Option<Piece>; (BOARD_WIDTH * BOARD_HEIGHT) as usize],
+        chess960: bool,
+    ) -> Result<CastlingRights, String> {
+        let mut castling_rights = CastlingRights::new();
+        for color in [PieceColor::White, PieceColor::Black] {
+            castling_rights.disable_king_castling(color);
+        }
+        if castling_rights_str == "-" {
+            return Ok(castling_rights);
+        }
+
+        let uses_standard = castling_rights_str
+            .chars()
+            .any(|ch| matches!(ch, 'K' | 'Q' | 'k' | 'q'));
+        let uses_files = castling_rights_str
+            .chars()
+            .any(|ch| matches!(ch, 'A'..='H' | 'a'..='h'));
+        if uses_standard && uses_files {
+            return Err("Castling rights can't mix KQkq with file letters".to_string());
+        }
+
+        for ch in castling_rights_str.chars() {
+            let color = if ch.is_ascii_uppercase() {
+                PieceColor::White
+            } else {
+                PieceColor::Black
+            };
+            let (kingside, rook_file) = match ch {
+                'K' | 'Q' | 'k' | 'q' => {
+                    let kingside = matches!(ch, 'K' | 'k');
+                    // In Chess960 the castling rooks are the outermost rooks on each side of the king
+                    let rook_file = if chess960 {
+                        let Some(rook_file) = Self::outermost_rook_file(pieces, color, kingside)
+                        else {
+                            return Err(format!("No castling rook for castling right: {}", ch));
+                        };
+                        rook_file
+                    } else {
+                        castling_rights.rook_file(color, kingside)
+                    };
+                    (kingside, rook_file)
+                }
+                'A'..='H' | 'a'..='h' => {
+                    let rook_file = (ch.to_ascii_lowercase() as i8) - 'a' as i8;
+                    let Some(king_file) = Self::back_rank_king_file(pieces, color) else {
+                        return Err(format!("No king on back rank for castling right: {}", ch));
+                    };
+                    if rook_file == king_file {
+                        return Err(format!("Invalid castling rook file: {}", ch));
+                    }
+                    (rook_file > king_file, rook_file)
                 }
+                _ => return Err(format!("Invalid castling character: {}", ch)),
+            };
+            if castling_rights.can_castle(color, kingside) {
+                return Err(format!("Duplicate castling right: {}", ch));
+            }
+            castling_rights.allow_castling(color, kingside, rook_file);
+        }
+
+        Ok(castling_rights)
+    }
+
+    fn back_rank_king_file(
+        pieces: &[Option<Piece>; (BOARD_WIDTH * BOARD_HEIGHT) as usize],
+        color: PieceColor,
+    ) -> Option<i8> {
+        let rank = match color {
+            PieceColor::White => 0,
+            PieceColor::Black => BOARD_HEIGHT - 1,
+        };
+        (0..BOARD_WIDTH).find(|&file| {
+            let index = Position::new(file, rank).to_index().unwrap();
+            matches!(
+                pieces[index],
+                Some(Piece {
+                    type_: PieceType::King,
+                    color: king_color,
+                }) if king_color == color
+            )
+        })
+    }
+
+    fn outermost_rook_file(
+        pieces: &[Option<Piece>; (BOARD_WIDTH * BOARD_HEIGHT) as usize],
+        color: PieceColor,
+        kingside: bool,
+    ) -> Option<i8> {
+        let rank = match color {
+            PieceColor::White => 0,
+            PieceColor::Black => BOARD_HEIGHT - 1,
+        };
+        let files: Vec<i8> = if kingside {
+            (0..BOARD_WIDTH).rev().collect()
+        } else {
+            (0..BOARD_WIDTH).collect()
+        };
+        // Walk from the edge towards the king and stop at the first rook
+        for file in files {
+            let index = Position::new(file, rank).to_index().ok()?;
+            match pieces[index] {
+                Some(Piece {
+                    type_: PieceType::Rook,
+                    color: rook_color,
+                }) if rook_color == color => return Some(file),
+                Some(Piece {
+                    type_: PieceType::King,
+                    ..
+                }) => return None,
+                _ => {}
             }
+        }
+        None
+    }
+
+    pub fn flipped(&self) -> Board {
+        let mut pieces = [const { None }; (BOARD_WIDTH * BOARD_HEIGHT) as usize];
+        for (index, piece) in self.pieces.iter().enumerate() {
+            let flipped_index = Position::from_index(index).flipped().to_index().unwrap();
+            pieces[flipped_index] = piece.map(|piece| Piece {
+                type_: piece.type_,
+                color: match piece.color {
+                    PieceColor::White => PieceColor::Black,
+                    PieceColor::Black => PieceColor::White,
+                },
+            });
+        }
+
+        let move_turn = match self.move_turn {
+            MoveTurn::White => MoveTurn::Black,
+            MoveTurn::Black => MoveTurn::White,
         };
 
-        Ok(Board::new(
+        let mut board = Board::new(
             pieces,
             move_turn,
-            castling_rights,
-            en_passant_target,
-        ))
+            self.castling_rights.flipped(),
+            self.en_passant_target.map(|pos| pos.flipped()),
+        );
+        board.promotion_move = self
+            .promotion_move
+            .map(|move_| Move::new(move_.from().flipped(), move_.to().flipped()));
+        board.chess960 = self.chess960;
+        board.halfmove_clock = self.halfmove_clock;
+        board.fullmove_number = self.fullmove_number;
+        board
+    }
+
+    // The pieces are hashed incrementally as they move, the rest is cheap to add on top
+    pub fn zobrist_hash(&self) -> u64 {
+        let mut hash = self.pieces_hash;
+        if let MoveTurn::Black = self.move_turn {
+            hash ^= zobrist::side_key();
+        }
+        for color in [PieceColor::White, PieceColor::Black] {
+            for kingside in [true, false] {
+                if self.castling_rights.can_castle(color, kingside) {
+                    hash ^= zobrist::castling_key(color, kingside);
+                }
+            }
+        }
+        // An en passant square nobody can capture on doesn't change the position
+        if let Some(target) = self.en_passant_target
+            && self.en_passant_target_valid()
+        {
+            hash ^= zobrist::en_passant_key(target.file);
+        }
+        hash
+    }
+
+    pub fn iter_pieces(&self) -> impl Iterator<Item = (Position, Piece)> {
+        self.pieces
+            .iter()
+            .enumerate()
+            .filter_map(|(index, piece)| piece.map(|piece| (Position::from_index(index), piece)))
+    }
+
+    // Owned copy of iter_pieces, for keeping a snapshot that doesn't borrow the board
+    pub fn piece_list(&self) -> Vec<(Position, Piece)> {
+        self.iter_pieces().collect()
+    }
+
+    // All 64 squares in index order, a1, b1, ..., h8
+    pub fn squares() -> impl Iterator<Item = Position> {
+        (0..(BOARD_WIDTH * BOARD_HEIGHT) as usize).map(Position::from_index)
+    }
+
+    pub fn find_pieces(&self, type_: PieceType, color: PieceColor) -> Vec<Position> {
+        self.iter_pieces()
+            .filter(|(_, piece)| piece.type_ == type_ && piece.color == color)
+            .map(|(pos, _)| pos)
+            .collect()
     }
 
     pub fn piece_at_pos(&self, pos: Position) -> Option<Piece> {
@@ -272,6 +1188,15 @@ impl Board {
         self.pieces[index]
     }
 
+    // Squares off the board have no piece, so they count as empty like in piece_at_pos
+    pub fn is_empty(&self, pos: Position) -> bool {
+        !self.is_occupied(pos)
+    }
+
+    pub fn is_occupied(&self, pos: Position) -> bool {
+        self.piece_at_pos(pos).is_some()
+    }
+
     fn cast_ray(
         &self,
         start_pos: Position,
@@ -294,60 +1219,328 @@ impl Board {
         }
     }
 
-    fn is_pos_attacked(&self, square_pos: Position, attacking_color: PieceColor) -> bool {
-        let knight_offsets = [
-            Offset::new(2, 1),
-            Offset::new(2, -1),
-            Offset::new(-2, 1),
-            Offset::new(-2, -1),
-            Offset::new(1, 2),
-            Offset::new(1, -2),
-            Offset::new(-1, 2),
-            Offset::new(-1, -2),
-        ];
-        let mut moves_and_pieces = Vec::<(Move, Piece)>::new();
-        for offset in knight_offsets {
-            let knight_pos = square_pos + offset;
-            if let Some(piece) = self.piece_at_pos(knight_pos) {
-                moves_and_pieces.push((Move::new(knight_pos, square_pos), piece));
-            }
-        }
-        let ray_directions = [
-            // Straight directions (rooks, queens)
-            Offset::new(1, 0),  // right
-            Offset::new(-1, 0), // left
-            Offset::new(0, 1),  // up
-            Offset::new(0, -1), // down
-            // Diagonal directions (bishops, queens)
-            Offset::new(1, 1),   // up-right
-            Offset::new(1, -1),  // down-right
-            Offset::new(-1, 1),  // up-left
-            Offset::new(-1, -1), // down-left
-        ];
-        for direction in ray_directions {
-            if let Ok((piece_pos, Some(piece))) = self.cast_ray(square_pos, direction) {
-                moves_and_pieces.push((Move::new(piece_pos, square_pos), piece));
+    // Squares reached by sliding from `from` in each direction, up to and including the first
+    // blocker unless it belongs to the piece on `from`
+    pub fn sliding_attacks(&self, from: Position, directions: &[Offset]) -> Vec<Position> {
+        let own_color = self.piece_at_pos(from).map(|piece| piece.color);
+        let mut attacks = Vec::new();
+        for &direction in directions {
+            let Ok((hit_pos, hit_piece)) = self.cast_ray(from, direction) else {
+                continue;
+            };
+            let Ok(mut ray) = Move::new(from, hit_pos).path() else {
+                continue;
+            };
+            if hit_piece.is_some_and(|piece| Some(piece.color) == own_color) {
+                ray.pop();
             }
+            attacks.extend(ray);
+        }
+        attacks
+    }
+
+    pub fn is_pinned(&self, pos: Position) -> bool {
+        self.pin_ray(pos).is_some()
+    }
+
+    // Direction from the king through the pinned piece towards the pinning slider,
+    // the pinned piece may only move along this line
+    pub fn pin_ray(&self, pos: Position) -> Option<Offset> {
+        let piece = self.piece_at_pos(pos)?;
+        if matches!(piece.type_, PieceType::King) {
+            return None;
+        }
+        let king_pos = self.find_king(piece.color)?;
+
+        let delta = king_pos.offset_to(pos);
+        let is_straight = delta.file == 0 || delta.rank == 0;
+        let is_diagonal = delta.file.abs() == delta.rank.abs();
+        if !is_straight && !is_diagonal {
+            return None;
+        }
+        let direction = Offset::new(delta.file.signum(), delta.rank.signum());
+
+        // The piece must be the first one seen from the king
+        let (first_pos, _) = self.cast_ray(king_pos, direction).ok()?;
+        if first_pos != pos {
+            return None;
         }
-        // Filter by attacking color and move validity
-        moves_and_pieces
+        let (_, Some(pinner)) = self.cast_ray(pos, direction).ok()? else {
+            return None;
+        };
+        let pins = match pinner.type_ {
+            PieceType::Queen => true,
+            PieceType::Rook => is_straight,
+            PieceType::Bishop => is_diagonal,
+            _ => false,
+        };
+        (pinner.color != piece.color && pins).then_some(direction)
+    }
+
+    pub fn attackers_of(&self, square_pos: Position, attacking_color: PieceColor) -> Vec<Position> {
+        self.attackers(square_pos, attacking_color).collect()
+    }
+
+    // Knights first, then the first piece along each ray, without allocating
+    fn attackers(
+        &self,
+        square_pos: Position,
+        attacking_color: PieceColor,
+    ) -> impl Iterator<Item = Position> + '_ {
+        let knights = Offset::KNIGHT_JUMPS
             .into_iter()
-            .filter(|(_, piece)| piece.color == attacking_color)
-            .any(|(move_, _)| self.move_pseudo_legal(move_))
+            .map(move |offset| square_pos + offset)
+            .filter(|&knight_pos| {
+                self.piece_at_pos(knight_pos)
+                    .is_some_and(|piece| piece.type_ == PieceType::Knight)
+            });
+        let sliders = Offset::DIRECTIONS.into_iter().filter_map(move |direction| {
+            match self.cast_ray(square_pos, direction) {
+                Ok((piece_pos, Some(_))) => Some(piece_pos),
+                _ => None,
+            }
+        });
+
+        knights
+            .chain(sliders)
+            .filter(move |&piece_pos| {
+                self.piece_at_pos(piece_pos)
+                    .is_some_and(|piece| piece.color == attacking_color)
+            })
+            .filter(move |&piece_pos| self.move_pseudo_legal(Move::new(piece_pos, square_pos)))
     }
 
-    fn find_king(&self, color: PieceColor) -> Option<Position> {
-        self.pieces
-            .iter()
-            .enumerate()
-            .filter_map(|(index, piece_option)| piece_option.as_ref().map(|piece| (index, piece)))
-            .find_map(|(index, piece)| {
-                if piece.color == color && matches!(piece.type_, PieceType::King) {
-                    Some(Position::from_index(index))
-                } else {
-                    None
+    // Material gain of the capture sequence started by move_, assuming both sides keep
+    // recapturing with their least valuable attacker for as long as it pays off
+    pub fn see(&self, move_: Move) -> i32 {
+        // The king can recapture, but only if nothing can take it back
+        let exchange_value = |piece_type: PieceType| match piece_type {
+            PieceType::King => 20_000,
+            _ => piece_type.value(),
+        };
+
+        let Some(mut attacker) = self.piece_at_pos(move_.from()) else {
+            return 0;
+        };
+        let target = move_.to();
+        let mut board = self.position_copy();
+        let mut gains = Vec::new();
+        if self.is_move_en_passant(move_) {
+            gains.push(PieceType::Pawn.value());
+            let _ = board.set(Position::new(target.file, move_.from().rank), None);
+        } else {
+            gains.push(
+                self.piece_at_pos(target)
+                    .map_or(0, |piece| piece.type_.value()),
+            );
+        }
+        let mut attacker_pos = move_.from();
+
+        loop {
+            // Removing the capturing piece uncovers any x-ray attackers behind it
+            let _ = board.set(attacker_pos, None);
+            let _ = board.set(target, Some(attacker));
+            let side = match attacker.color {
+                PieceColor::White => PieceColor::Black,
+                PieceColor::Black => PieceColor::White,
+            };
+
+            let Some((next_pos, next_attacker)) = board
+                .attackers_of(target, side)
+                .into_iter()
+                .filter_map(|pos| board.piece_at_pos(pos).map(|piece| (pos, piece)))
+                .min_by_key(|(_, piece)| exchange_value(piece.type_))
+            else {
+                break;
+            };
+
+            let previous_gain = *gains.last().unwrap();
+            gains.push(exchange_value(attacker.type_) - previous_gain);
+            // Neither side can do better by continuing, stop early
+            if (-previous_gain).max(*gains.last().unwrap()) < 0 {
+                break;
+            }
+            attacker = next_attacker;
+            attacker_pos = next_pos;
+        }
+
+        // Each side may stop capturing whenever continuing would lose material
+        for index in (1..gains.len()).rev() {
+            gains[index - 1] = -(-gains[index - 1]).max(gains[index]);
+        }
+        gains[0]
+    }
+
+    // Pieces of color that are attacked and either undefended or attacked by something
+    // cheaper than themselves. Kings are left out since an attacked king is in check.
+    pub fn hanging_pieces(&self, color: PieceColor) -> Vec<Position> {
+        let enemy = match color {
+            PieceColor::White => PieceColor::Black,
+            PieceColor::Black => PieceColor::White,
+        };
+        self.iter_pieces()
+            .filter(|(_, piece)| piece.color == color && piece.type_ != PieceType::King)
+            .filter(|&(pos, piece)| {
+                let attackers = self.attackers_of(pos, enemy);
+                if attackers.is_empty() {
+                    return false;
+                }
+                // Pawns only defend diagonally by capturing, so ask who could retake an enemy
+                // piece standing on the square
+                let mut board = self.position_copy();
+                let _ = board.set(
+                    pos,
+                    Some(Piece {
+                        type_: piece.type_,
+                        color: enemy,
+                    }),
+                );
+                if board.attackers_of(pos, color).is_empty() {
+                    return true;
                 }
+                // The king can't take a defended piece
+                attackers.into_iter().any(|attacker_pos| {
+                    self.piece_at_pos(attacker_pos).is_some_and(|attacker| {
+                        attacker.type_ != PieceType::King
+                            && attacker.type_.value() < piece.type_.value()
+                    })
+                })
             })
+            .map(|(pos, _)| pos)
+            .collect()
+    }
+
+    // Captures first by most valuable victim then least valuable attacker, then
+    // promotions, then quiet moves in their original order
+    pub fn order_moves(&self, moves: &mut [Move]) {
+        moves.sort_by_key(|&move_| {
+            let attacker_value = self
+                .piece_at_pos(move_.from())
+                .map_or(0, |piece| piece.type_.value());
+            let score = if self.is_move_capture(move_) {
+                let victim_value = if self.is_move_en_passant(move_) {
+                    PieceType::Pawn.value()
+                } else {
+                    self.piece_at_pos(move_.to())
+                        .map_or(0, |piece| piece.type_.value())
+                };
+                20_000 + victim_value * 10 - attacker_value
+            } else if self.is_promotion_move(move_) {
+                10_000
+            } else {
+                0
+            };
+            std::cmp::Reverse(score)
+        });
+    }
+
+    fn is_pos_attacked(&self, square_pos: Position, attacking_color: PieceColor) -> bool {
+        self.attackers(square_pos, attacking_color).next().is_some()
+    }
+
+    fn find_king(&self, color: PieceColor) -> Option<Position> {
+        if !self.kings_cached {
+            return self.scan_for_king(color);
+        }
+        let king = match color {
+            PieceColor::White => self.white_king,
+            PieceColor::Black => self.black_king,
+        };
+        debug_assert_eq!(king, self.scan_for_king(color), "King cache is out of date");
+        king
+    }
+
+    // Fills the king cache from a scan. A side with several kings has no single king to track,
+    // so such boards keep scanning instead.
+    fn cache_kings(&mut self) {
+        self.white_king = self.scan_for_king(PieceColor::White);
+        self.black_king = self.scan_for_king(PieceColor::Black);
+        self.kings_cached = [PieceColor::White, PieceColor::Black]
+            .into_iter()
+            .all(|color| {
+                self.iter_pieces()
+                    .filter(|(_, piece)| piece.color == color && piece.type_ == PieceType::King)
+                    .count()
+                    <= 1
+            });
+    }
+
+    // Moves update the cache themselves, set leaves it alone
+    fn set_king(&mut self, color: PieceColor, pos: Option<Position>) {
+        match color {
+            PieceColor::White => self.white_king = pos,
+            PieceColor::Black => self.black_king = pos,
+        }
+    }
+
+    pub fn king_position(&self, color: PieceColor) -> Option<Position> {
+        self.find_king(color)
+    }
+
+    fn scan_for_king(&self, color: PieceColor) -> Option<Position> {
+        self.iter_pieces().find_map(|(pos, piece)| {
+            if piece.color == color && matches!(piece.type_, PieceType::King) {
+                Some(pos)
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn turn(&self) -> PieceColor {
+        match self.move_turn {
+            MoveTurn::White => PieceColor::White,
+            MoveTurn::Black => PieceColor::Black,
+        }
+    }
+
+    // Only flips the side to move, the rest of the state is kept as it is
+    pub fn set_turn(&mut self, color: PieceColor) {
+        self.move_turn = match color {
+            PieceColor::White => MoveTurn::White,
+            PieceColor::Black => MoveTurn::Black,
+        };
+    }
+
+    pub fn halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+
+    pub fn fullmove_number(&self) -> u32 {
+        self.fullmove_number
+    }
+
+    // Half-moves played since the start of the game, going by the move number and turn
+    pub fn ply(&self) -> u32 {
+        let black_to_move = match self.move_turn {
+            MoveTurn::White => 0,
+            MoveTurn::Black => 1,
+        };
+        (self.fullmove_number - 1) * 2 + black_to_move
+    }
+
+    pub fn castling_rights(&self) -> CastlingRights {
+        self.castling_rights
+    }
+
+    // Castling still needs the king and rook on their squares to be legal
+    pub fn set_castling_rights(&mut self, rights: CastlingRights) {
+        self.castling_rights = rights;
+    }
+
+    pub(crate) fn set_en_passant_target(&mut self, target: Option<Position>) {
+        self.en_passant_target = target;
+    }
+
+    // The square of the pawn that can be taken en passant, one step past the target square
+    // in the direction it moved
+    pub fn en_passant_victim(&self) -> Option<Position> {
+        let pushed_color = match self.move_turn {
+            MoveTurn::White => PieceColor::Black,
+            MoveTurn::Black => PieceColor::White,
+        };
+        self.en_passant_target
+            .map(|target| target + pushed_color.pawn_direction())
     }
 
     pub fn is_in_check(&self) -> bool {
@@ -362,7 +1555,11 @@ impl Board {
         let Some(king_pos) = self.find_king(color) else {
             return false;
         };
+        self.is_in_check_at(color, king_pos)
+    }
 
+    // For callers that already know where the king of the given color stands
+    pub fn is_in_check_at(&self, color: PieceColor, king_pos: Position) -> bool {
         let attacking_color = match color {
             PieceColor::White => PieceColor::Black,
             PieceColor::Black => PieceColor::White,
@@ -371,16 +1568,32 @@ impl Board {
         self.is_pos_attacked(king_pos, attacking_color)
     }
 
-    fn path_clear(&self, move_: Move) -> bool {
-        let Some(moving_piece) = self.piece_at_pos(move_.from()) else {
-            return false;
+    pub fn is_double_check(&self) -> bool {
+        self.checkers(self.turn()).len() >= 2
+    }
+
+    // Squares of the enemy pieces giving check to the king of the given color
+    pub fn checkers(&self, color: PieceColor) -> Vec<Position> {
+        let Some(king_pos) = self.find_king(color) else {
+            return Vec::new();
+        };
+        let attacking_color = match color {
+            PieceColor::White => PieceColor::Black,
+            PieceColor::Black => PieceColor::White,
         };
-        let Some(_) = move_.shape() else {
+        self.attackers_of(king_pos, attacking_color)
+    }
+
+    // Walks the squares between from and to for the already computed shape of move_
+    fn path_clear(&self, move_: Move, shape: MoveShape) -> bool {
+        let Some(moving_piece) = self.piece_at_pos(move_.from()) else {
             return false;
         };
         // Check if path is clear (excluding destination)
-        if let Ok(path) = move_.path_excluding_destination() {
-            if path.into_iter().any(|pos| self.piece_at_pos(pos).is_some()) {
+        if let MoveShape::Straight(data) | MoveShape::Diagonal(data) = shape {
+            let delta = move_.from().offset_to(move_.to());
+            let step = Offset::new(delta.file.signum(), delta.rank.signum());
+            if (1..data.distance).any(|distance| self.is_occupied(move_.from() + step * distance)) {
                 return false;
             }
         }
@@ -418,7 +1631,8 @@ impl Board {
         if !matches!(moving_piece.type_, PieceType::Pawn) {
             return false;
         }
-        match (move_.shape(), moving_piece.color) {
+        matches!(
+            (move_.shape(), moving_piece.color),
             (
                 Some(MoveShape::Diagonal(ShapeData {
                     forward_only: true,
@@ -426,17 +1640,56 @@ impl Board {
                     ..
                 })),
                 PieceColor::White,
-            ) => true,
-            (
+            ) | (
                 Some(MoveShape::Diagonal(ShapeData {
                     backward_only: true,
                     distance: 1,
                     ..
                 })),
                 PieceColor::Black,
-            ) => true,
-            _ => false,
+            )
+        )
+    }
+
+    fn en_passant_target_valid(&self) -> bool {
+        let Some(target) = self.en_passant_target else {
+            return true;
+        };
+
+        // The pawn that just moved two squares belongs to the side that isn't moving
+        let (moving_color, pushed_color, target_rank, direction) = match self.move_turn {
+            MoveTurn::White => (PieceColor::White, PieceColor::Black, 5, -1),
+            MoveTurn::Black => (PieceColor::Black, PieceColor::White, 2, 1),
+        };
+        if target.rank != target_rank {
+            return false;
+        }
+
+        let pushed_pawn_pos = Position::new(target.file, target.rank + direction);
+        let pushed_from_pos = Position::new(target.file, target.rank - direction);
+        if self.is_occupied(target) || self.is_occupied(pushed_from_pos) {
+            return false;
         }
+        if !matches!(
+            self.piece_at_pos(pushed_pawn_pos),
+            Some(Piece {
+                type_: PieceType::Pawn,
+                color,
+            }) if color == pushed_color
+        ) {
+            return false;
+        }
+
+        // An enemy pawn must stand next to the pushed pawn to capture it
+        [-1, 1].into_iter().any(|file_offset| {
+            matches!(
+                self.piece_at_pos(pushed_pawn_pos + Offset::EAST * file_offset),
+                Some(Piece {
+                    type_: PieceType::Pawn,
+                    color,
+                }) if color == moving_color
+            )
+        })
     }
 
     fn move_pseudo_legal(&self, move_: Move) -> bool {
@@ -447,7 +1700,18 @@ impl Board {
             return false;
         };
 
-        let Some(shape) = move_.shape() else {
+        // The shape is computed once here and handed to the helpers below
+        let shape = move_.shape();
+
+        // Special king movement rules (castling), a castling move that isn't valid is rejected
+        // here rather than being checked as an ordinary king move
+        if let PieceType::King = moving_piece.type_
+            && let Some(castling_side) = self.castling_for_shape(move_, shape)
+        {
+            return self.validate_castling(move_, castling_side);
+        }
+
+        let Some(shape) = shape else {
             return false;
         };
         if !moving_piece.shape_allowed(shape) {
@@ -455,36 +1719,51 @@ impl Board {
         }
 
         // Special pawn movement rules
-        if let PieceType::Pawn = moving_piece.type_ {
-            if !moving_piece.validate_pawn_rules(move_, self.is_move_capture(move_)) {
-                return false;
-            }
+        if let PieceType::Pawn = moving_piece.type_
+            && !moving_piece.validate_pawn_shape(move_.from(), shape, self.is_move_capture(move_))
+        {
+            return false;
         }
 
-        // Special king movement rules (castling)
-        if let PieceType::King = moving_piece.type_ {
-            if self.get_castling(move_).is_some() {
-                return self.validate_castling(move_);
-            }
-        }
+        self.path_clear(move_, shape)
+    }
 
-        self.path_clear(move_)
+    // The side castled on if move_ is a legal castling move
+    pub fn is_castle(&self, move_: Move) -> Option<CastlingSide> {
+        if !self.move_legal(move_) {
+            return None;
+        }
+        self.get_castling(move_)
     }
 
     fn get_castling(&self, move_: Move) -> Option<CastlingSide> {
-        let Some(moving_piece) = self.piece_at_pos(move_.from()) else {
-            return None;
-        };
+        self.castling_for_shape(move_, move_.shape())
+    }
+
+    fn castling_for_shape(&self, move_: Move, shape: Option<MoveShape>) -> Option<CastlingSide> {
+        let moving_piece = self.piece_at_pos(move_.from())?;
 
         if !matches!(moving_piece.type_, PieceType::King) {
             return None;
         }
 
-        let Some(shape) = move_.shape() else {
-            return None;
-        };
+        // In Chess960 castling is played as the king capturing its own rook
+        if self.chess960 {
+            let target_piece = self.piece_at_pos(move_.to())?;
+            if !matches!(target_piece.type_, PieceType::Rook)
+                || target_piece.color != moving_piece.color
+                || move_.to().rank != move_.from().rank
+            {
+                return None;
+            }
+            if move_.to().file > move_.from().file {
+                return Some(CastlingSide::Kingside);
+            } else {
+                return Some(CastlingSide::Queenside);
+            }
+        }
 
-        if matches!(shape, MoveShape::Straight(ShapeData { distance: 2, .. })) {
+        if matches!(shape?, MoveShape::Straight(ShapeData { distance: 2, .. })) {
             if move_.to().file > move_.from().file {
                 Some(CastlingSide::Kingside)
             } else {
@@ -495,164 +1774,383 @@ impl Board {
         }
     }
 
-    fn validate_castling(&self, move_: Move) -> bool {
+    fn validate_castling(&self, move_: Move, castling_side: CastlingSide) -> bool {
         let Some(moving_piece) = self.piece_at_pos(move_.from()) else {
             return false;
         };
 
-        let Some(castling_side) = self.get_castling(move_) else {
+        if self
+            .castling_obstacle(moving_piece.color, move_.from(), castling_side)
+            .is_err()
+        {
             return false;
-        };
+        }
 
-        // Check castling rights
+        // Standard castling moves the king onto its target, Chess960 moves it onto the rook
+        let rank = move_.from().rank;
         let is_kingside = matches!(castling_side, CastlingSide::Kingside);
-        if !self
-            .castling_rights
-            .can_castle(moving_piece.color, is_kingside)
-        {
-            return false;
+        let rook_pos = Position::new(
+            self.castling_rights
+                .rook_file(moving_piece.color, is_kingside),
+            rank,
+        );
+        let king_to = castling_side.king_target(moving_piece.color);
+        let expected_to = if self.chess960 { rook_pos } else { king_to };
+        move_.to() == expected_to
+    }
+
+    // Why the side to move can't castle on the given side right now, if anything stops it
+    pub fn castle_legality(&self, side: CastlingSide) -> Result<(), CastleError> {
+        let color = self.turn();
+        match self.king_position(color) {
+            Some(king_pos) if king_pos.rank == back_rank(color) => {
+                self.castling_obstacle(color, king_pos, side)
+            }
+            _ => Err(CastleError::NoRights),
         }
+    }
 
-        // Check path is clear by casting ray from rook to king
-        let rook_file = if is_kingside { 7 } else { 0 };
-        let rook_pos = Position::new(rook_file, move_.from().rank);
+    // Whether the side to move can castle on the given side this turn. Unlike
+    // CastlingRights::can_castle this also checks the path and attacked squares.
+    pub fn can_castle_now(&self, side: CastlingSide) -> bool {
+        self.castle_legality(side).is_ok()
+    }
 
-        // Cast ray from rook toward king
-        let direction = if is_kingside {
-            Offset::new(-1, 0)
-        } else {
-            Offset::new(1, 0)
-        };
+    // Checks for castling with the king on king_pos, in the order the reasons are reported
+    fn castling_obstacle(
+        &self,
+        color: PieceColor,
+        king_pos: Position,
+        castling_side: CastlingSide,
+    ) -> Result<(), CastleError> {
+        let is_kingside = matches!(castling_side, CastlingSide::Kingside);
+        if !self.castling_rights.can_castle(color, is_kingside) {
+            return Err(CastleError::NoRights);
+        }
 
-        if let Ok((
-            _,
-            Some(Piece {
-                type_: PieceType::King,
-                ..
-            }),
-        )) = self.cast_ray(rook_pos, direction)
+        let rank = king_pos.rank;
+        let rook_pos = Position::new(self.castling_rights.rook_file(color, is_kingside), rank);
+        if self.piece_at_pos(rook_pos)
+            != Some(Piece {
+                type_: PieceType::Rook,
+                color,
+            })
         {
-            // Path is clear and king is reachable
-        } else {
-            return false;
+            return Err(CastleError::RookMissing);
+        }
+
+        // Every square the king and rook travel over must be empty, apart from the two castling pieces
+        let king_to = castling_side.king_target(color);
+        let rook_to = castling_side.rook_target(color);
+        let (Ok(king_path), Ok(rook_path)) = (
+            Move::new(king_pos, king_to).path(),
+            Move::new(rook_pos, rook_to).path(),
+        ) else {
+            return Err(CastleError::PathBlocked);
+        };
+        if king_path
+            .iter()
+            .chain(rook_path.iter())
+            .filter(|&&pos| pos != king_pos && pos != rook_pos)
+            .any(|&pos| self.is_occupied(pos))
+        {
+            return Err(CastleError::PathBlocked);
         }
 
-        // Check king doesn't travel through or end in check
-        let attacking_color = match moving_piece.color {
+        let attacking_color = match color {
             PieceColor::White => PieceColor::Black,
             PieceColor::Black => PieceColor::White,
         };
-
-        // Check that king doesn't travel through or end in check
-        if self.is_pos_attacked(move_.from(), attacking_color) {
-            return false;
+        if self.is_pos_attacked(king_pos, attacking_color) {
+            return Err(CastleError::InCheck);
+        }
+        if king_path
+            .iter()
+            .filter(|&&pos| pos != king_to)
+            .any(|&pos| self.is_pos_attacked(pos, attacking_color))
+        {
+            return Err(CastleError::ThroughCheck);
+        }
+        if self.is_pos_attacked(king_to, attacking_color) {
+            return Err(CastleError::IntoCheck);
         }
+        Ok(())
+    }
 
-        let Ok(path) = move_.path() else {
-            return false;
+    // Checks a move given as squares, like a piece dropped in a GUI. Castling is given as the
+    // king move, and a promotion without a piece is legal when some promotion would be.
+    pub fn is_legal(&self, from: Position, to: Position, promotion: Option<PieceType>) -> bool {
+        let move_ = match promotion {
+            Some(piece_type) => Move::new_promotion(from, to, piece_type),
+            None => Move::new(from, to),
         };
-
-        !path
-            .into_iter()
-            .any(|pos| self.is_pos_attacked(pos, attacking_color))
+        self.move_legal(move_)
     }
 
     pub fn move_legal(&self, move_: Move) -> bool {
-        let Some(moving_piece) = self.piece_at_pos(move_.from()) else {
+        if !self.move_playable(move_) {
             return false;
-        };
-        let current_color = match self.move_turn {
-            MoveTurn::White => PieceColor::White,
-            MoveTurn::Black => PieceColor::Black,
-        };
-        if moving_piece.color != current_color {
+        }
+        // Only the position itself is copied, not the move history, so nothing is allocated.
+        // move_legal_in_place avoids the copy when the board can be borrowed mutably.
+        let mut test_board = self.position_copy();
+        if test_board.execute_move(move_).is_err() {
             return false;
         }
 
-        if !self.move_pseudo_legal(move_) {
+        !test_board.is_color_in_check(self.turn())
+    }
+
+    // Same as move_legal, but plays the move on this board and unmakes it again
+    pub(crate) fn move_legal_in_place(&mut self, move_: Move) -> bool {
+        if !self.move_playable(move_) {
             return false;
         }
-        let mut test_board = self.clone();
-        if let Err(_) = test_board.execute_move(move_) {
+        let color = self.turn();
+        // Like play_move and unmake_move, minus the history they keep
+        let Ok(undo) = self.undo_for(move_) else {
+            return false;
+        };
+        if self.execute_move(move_).is_err() {
             return false;
         }
-
-        !test_board.is_color_in_check(current_color)
+        let in_check = self
+            .king_position(color)
+            .is_some_and(|king_pos| self.is_in_check_at(color, king_pos));
+        self.restore(undo);
+        !in_check
     }
 
-    pub fn legal_moves(&self, pos: Position) -> Vec<Position> {
-        let Some(_piece) = self.piece_at_pos(pos) else {
-            return Vec::new();
+    // Everything move_legal checks except whether the mover's king is left in check
+    fn move_playable(&self, move_: Move) -> bool {
+        let Some(moving_piece) = self.piece_at_pos(move_.from()) else {
+            return false;
         };
+        if moving_piece.color != self.turn() {
+            return false;
+        }
 
-        let knight_offsets = [
-            Offset::new(2, 1),
-            Offset::new(2, -1),
-            Offset::new(-2, 1),
-            Offset::new(-2, -1),
-            Offset::new(1, 2),
-            Offset::new(1, -2),
-            Offset::new(-1, 2),
-            Offset::new(-1, -2),
-        ];
-
-        let ray_directions = [
-            Offset::new(1, 0),
-            Offset::new(-1, 0),
-            Offset::new(0, 1),
-            Offset::new(0, -1),
-            Offset::new(1, 1),
-            Offset::new(1, -1),
-            Offset::new(-1, 1),
-            Offset::new(-1, -1),
-        ];
+        if let Some(promotion) = move_.promotion()
+            && (!self.is_promotion_move(move_)
+                || matches!(promotion, PieceType::Pawn | PieceType::King))
+        {
+            return false;
+        }
 
-        let knight_moves = knight_offsets
-            .into_iter()
-            .map(|offset| pos + offset)
-            .map(|to_pos| Move::new(pos, to_pos));
+        self.move_pseudo_legal(move_)
+    }
 
-        let sliding_moves = ray_directions
+    // Knight jumps and every square along each ray up to the first piece, checked for
+    // legality by the caller
+    fn candidate_moves(&self, pos: Position) -> impl Iterator<Item = Move> + '_ {
+        let knight_moves = Offset::KNIGHT_JUMPS
             .into_iter()
-            .filter_map(|dir| self.cast_ray(pos, dir).ok())
-            .map(|(hit_pos, _piece)| Move::new(pos, hit_pos))
-            .filter_map(|move_| move_.path().ok())
-            .flatten()
-            .map(|target_pos| Move::new(pos, target_pos));
+            .map(move |offset| pos + offset)
+            .filter(|to_pos| to_pos.is_on_board());
+
+        let sliding_moves = Offset::DIRECTIONS.into_iter().flat_map(move |direction| {
+            let mut blocked = false;
+            (1..BOARD_WIDTH)
+                .map(move |distance| pos + direction * distance)
+                .take_while(move |&to_pos| {
+                    if blocked || !to_pos.is_on_board() {
+                        return false;
+                    }
+                    blocked = self.is_occupied(to_pos);
+                    true
+                })
+        });
 
         knight_moves
             .chain(sliding_moves)
+            .map(move |to_pos| Move::new(pos, to_pos))
+    }
+
+    pub fn legal_moves(&self, pos: Position) -> Vec<Position> {
+        if self.is_empty(pos) {
+            return Vec::new();
+        }
+
+        self.candidate_moves(pos)
             .filter(|&move_| self.move_legal(move_))
             .map(|move_| move_.to())
             .collect()
     }
 
-    pub fn all_legal_moves(&self) -> Vec<Move> {
-        let current_color = match self.move_turn {
-            MoveTurn::White => PieceColor::White,
-            MoveTurn::Black => PieceColor::Black,
+    // Full moves from a square, with one move per promotion piece
+    pub fn legal_moves_from(&self, pos: Position) -> Vec<Move> {
+        let Some(piece) = self.piece_at_pos(pos) else {
+            return Vec::new();
         };
+        if piece.color != self.turn() {
+            return Vec::new();
+        }
 
-        self.pieces
-            .iter()
-            .enumerate()
-            .filter(|(_index, piece)| piece.is_some())
-            .map(|(index, piece)| (index, piece.unwrap()))
-            .filter(|(_index, piece)| piece.color == current_color)
-            .map(|(index, _piece)| Position::from_index(index))
-            .map(|pos| {
-                self.legal_moves(pos)
-                    .into_iter()
-                    .map(|to_pos| Move::new(pos, to_pos))
-                    .collect::<Vec<Move>>()
-            })
-            .flatten()
+        self.candidate_moves(pos)
+            .filter(|&move_| self.move_legal(move_))
+            .flat_map(|move_| self.with_promotions(move_))
             .collect()
     }
 
-    fn move_piece(&mut self, from: Position, to: Position) -> Result<(), String> {
-        let piece = self.piece_at_pos(from);
-        self.set(to, piece)?;
+    // The move itself, or one move per promotion piece if it promotes without naming one
+    fn with_promotions(&self, move_: Move) -> impl Iterator<Item = Move> + use<> {
+        let promotions: &[Option<PieceType>] =
+            if self.is_promotion_move(move_) && move_.promotion().is_none() {
+                &[
+                    Some(PieceType::Queen),
+                    Some(PieceType::Rook),
+                    Some(PieceType::Bishop),
+                    Some(PieceType::Knight),
+                ]
+            } else {
+                &[None]
+            };
+        promotions.iter().map(move |promotion| match promotion {
+            Some(piece_type) => Move::new_promotion(move_.from(), move_.to(), *piece_type),
+            None => move_,
+        })
+    }
+
+    // Legal moves of the side to move grouped by origin square, squares without moves left out
+    pub fn legal_moves_grouped(&self) -> Vec<(Position, Vec<Move>)> {
+        // legal_moves_iter generates each piece's moves together, so a new group starts
+        // whenever the origin square changes
+        let mut grouped: Vec<(Position, Vec<Move>)> = Vec::new();
+        for move_ in self.legal_moves_iter() {
+            match grouped.last_mut() {
+                Some((from, moves)) if *from == move_.from() => moves.push(move_),
+                _ => grouped.push((move_.from(), vec![move_])),
+            }
+        }
+        grouped
+    }
+
+    // Whether the move puts the opponent in check, promotions without a piece promote to a queen
+    pub fn gives_check(&self, move_: Move) -> bool {
+        if !self.move_legal(move_) {
+            return false;
+        }
+        let move_ = if self.is_promotion_move(move_) && move_.promotion().is_none() {
+            Move::new_promotion(move_.from(), move_.to(), PieceType::Queen)
+        } else {
+            move_
+        };
+        let mut board = self.position_copy();
+        if board.execute_move(move_).is_err() {
+            return false;
+        }
+        board.is_in_check()
+    }
+
+    pub fn move_stats(&self) -> MoveStats {
+        let mut stats = MoveStats::default();
+        for move_ in self.legal_moves_iter() {
+            stats.total += 1;
+            if self.is_move_capture(move_) {
+                stats.captures += 1;
+            }
+            // The moves are already legal, so each is only played once to look for check
+            let mut board = self.position_copy();
+            if board.execute_move(move_).is_ok() && board.is_in_check() {
+                stats.checks += 1;
+            }
+            if self.is_promotion_move(move_) {
+                stats.promotions += 1;
+            }
+        }
+        stats
+    }
+
+    // Same as all_legal_moves().len(), without collecting the moves
+    pub fn count_legal_moves(&self) -> usize {
+        self.legal_moves_iter().count()
+    }
+
+    pub fn all_legal_moves(&self) -> Vec<Move> {
+        self.legal_moves_iter().collect()
+    }
+
+    // Same moves as all_legal_moves, checked by playing and unmaking each one on this board
+    pub(crate) fn legal_moves_in_place(&mut self) -> Vec<Move> {
+        let current_color = self.turn();
+        let candidates: Vec<Move> = self
+            .iter_pieces()
+            .filter(|(_, piece)| piece.color == current_color)
+            .flat_map(|(pos, _)| self.candidate_moves(pos))
+            .collect();
+        let legal_moves: Vec<Move> = candidates
+            .into_iter()
+            .filter(|&move_| self.move_legal_in_place(move_))
+            .collect();
+        legal_moves
+            .into_iter()
+            .flat_map(|move_| self.with_promotions(move_))
+            .collect()
+    }
+
+    // Generates legal moves one at a time, so stopping early skips the remaining legality checks.
+    // Promotions come once per promotion piece, so the moves can be matched against a full move.
+    pub fn legal_moves_iter(&self) -> impl Iterator<Item = Move> + '_ {
+        let current_color = self.turn();
+        self.iter_pieces()
+            .filter(move |(_, piece)| piece.color == current_color)
+            .flat_map(|(pos, _)| self.candidate_moves(pos))
+            .filter(|&move_| self.move_legal(move_))
+            .flat_map(|move_| self.with_promotions(move_))
+    }
+
+    // Number of legal moves for color as if it were their turn, pinned pieces only count
+    // moves along the pin and a side in check only counts moves that get out of check
+    pub fn mobility(&self, color: PieceColor) -> usize {
+        if color == self.turn() {
+            return self.count_legal_moves();
+        }
+        self.with_mover(color).count_legal_moves()
+    }
+
+    // Legal moves for color as if it were their turn, moves that leave color's own king in
+    // check are left out
+    pub fn legal_moves_for(&self, color: PieceColor) -> Vec<Move> {
+        if color == self.turn() {
+            return self.all_legal_moves();
+        }
+        self.with_mover(color).all_legal_moves()
+    }
+
+    // Copy of just the position, without the move history or a pending promotion. Cheaper than
+    // clone for boards that are only used to look ahead.
+    pub(crate) fn position_copy(&self) -> Board {
+        Board {
+            promotion_move: None,
+            hash_history: Vec::new(),
+            null_move_en_passant: Vec::new(),
+            undo_history: Vec::new(),
+            ..*self
+        }
+    }
+
+    // Copy of the board with color to move
+    fn with_mover(&self, color: PieceColor) -> Board {
+        let mut board = self.position_copy();
+        board.set_turn(color);
+        // The en passant target and a pending promotion belong to the side that was to move
+        board.en_passant_target = None;
+        board.promotion_move = None;
+        board
+    }
+
+    #[cfg(feature = "rand")]
+    pub fn random_move(&self, rng: &mut impl rand::Rng) -> Option<Move> {
+        use rand::seq::IndexedRandom;
+
+        self.all_legal_moves().choose(rng).copied()
+    }
+
+    fn move_piece(&mut self, from: Position, to: Position) -> Result<(), String> {
+        let Some(piece) = self.piece_at_pos(from) else {
+            return Err("No piece to move".to_string());
+        };
+        self.set(to, Some(piece))?;
         self.set(from, None)?;
         Ok(())
     }
@@ -672,92 +2170,353 @@ impl Board {
             return MoveResult::Promotion;
         }
 
-        if let Err(_) = self.execute_move(move_) {
+        if self.play_move(move_).is_err() {
             return MoveResult::Illegal;
         }
         MoveResult::Normal
     }
 
-    fn execute_move(&mut self, move_: Move) -> Result<(), String> {
-        // Move the rook if castling
-        if let Some(castling_side) = self.get_castling(move_) {
-            let (rook_from_file, rook_to_file) = match castling_side {
-                CastlingSide::Kingside => (7, 5),  // h->f
-                CastlingSide::Queenside => (0, 3), // a->d
-            };
-
-            let rook_from = Position::new(rook_from_file, move_.from().rank);
-            let rook_to = Position::new(rook_to_file, move_.from().rank);
-            self.move_piece(rook_from, rook_to)?;
+    // Like make_move, but takes a full move and promotes right away instead of in two steps
+    pub fn make_move_checked(&mut self, move_: Move) -> Result<MoveInfo, ChessError> {
+        if self.promotion_move.is_some() {
+            return Err(ChessError::PromotionPending);
+        }
+        if !move_.is_on_board() {
+            return Err(ChessError::IllegalMove(move_));
+        }
+        let Some(piece) = self.piece_at_pos(move_.from()) else {
+            return Err(ChessError::NoPieceAtSource(move_.from()));
+        };
+        if !self.move_legal(move_) {
+            return Err(ChessError::IllegalMove(move_));
         }
+        if self.is_promotion_move(move_) && move_.promotion().is_none() {
+            return Err(ChessError::PromotionRequired(move_));
+        }
+        let is_castling = self.get_castling(move_).is_some();
+        let is_en_passant = self.is_move_en_passant(move_);
+        let captured = if is_castling {
+            None
+        } else if is_en_passant {
+            self.piece_at_pos(Position::new(move_.to().file, move_.from().rank))
+        } else {
+            self.piece_at_pos(move_.to())
+        };
 
-        if self.is_move_en_passant(move_) {
-            let captured_pawn_pos = Position::new(move_.to().file, move_.from().rank);
-            self.set(captured_pawn_pos, None)?;
+        self.play_move(move_)
+            .map_err(|_| ChessError::IllegalMove(move_))?;
+
+        Ok(MoveInfo {
+            move_,
+            piece,
+            captured,
+            is_castling,
+            is_en_passant,
+        })
+    }
+
+    // Plays a full move and returns the captured piece, including an en passant victim.
+    // make_move keeps returning MoveResult for its two-step promotion flow.
+    pub fn make_move_with_capture(&mut self, move_: Move) -> Result<Option<Piece>, ChessError> {
+        self.make_move_checked(move_).map(|info| info.captured)
+    }
+
+    // Plays a full move and returns the status of the resulting position
+    pub fn make_move_with_status(&mut self, move_: Move) -> Result<GameStatus, ChessError> {
+        self.make_move_checked(move_)?;
+        Ok(self.status())
+    }
+
+    // Plays the moves in order, on a failure the moves already played are taken back
+    pub fn make_moves(&mut self, moves: &[Move]) -> Result<(), ChessError> {
+        for (index, &move_) in moves.iter().enumerate() {
+            if self.make_move_checked(move_).is_err() {
+                for _ in 0..index {
+                    self.unmake_move();
+                }
+                return Err(ChessError::IllegalMoveInLine(index, move_));
+            }
         }
+        Ok(())
+    }
 
-        self.move_piece(move_.from(), move_.to())?;
+    pub fn make_uci_move(&mut self, uci: &str) -> Result<(), ChessError> {
+        let move_ = Move::from_uci(uci)?;
+        self.make_move_checked(move_)?;
+        Ok(())
+    }
 
-        self.update_castling_rights_for_move(move_);
-        self.update_en_passant_target(move_);
-        self.move_turn = match self.move_turn {
-            MoveTurn::White => MoveTurn::Black,
-            MoveTurn::Black => MoveTurn::White,
+    // The arguments of a UCI position command, `startpos moves e2e4 ...` or
+    // `fen <6 fields> moves ...`, with the moves played on the resulting board
+    pub fn from_uci_position(tokens: &str) -> Result<Self, ChessError> {
+        let invalid = |reason: &str| ChessError::InvalidPosition(reason.to_string());
+        let mut tokens = tokens.split_whitespace();
+
+        let board = match tokens.next() {
+            Some("startpos") => Self::starting_position(),
+            Some("fen") => {
+                let fields: Vec<&str> = tokens
+                    .by_ref()
+                    .take_while(|&token| token != "moves")
+                    .collect();
+                let board =
+                    Self::from_fen(&fields.join(" ")).map_err(ChessError::InvalidPosition)?;
+                // take_while already used up the moves keyword
+                return board.with_uci_moves(tokens);
+            }
+            _ => return Err(invalid("Expected startpos or fen")),
         };
 
+        match tokens.next() {
+            None => Ok(board),
+            Some("moves") => board.with_uci_moves(tokens),
+            Some(_) => Err(invalid("Expected moves after the position")),
+        }
+    }
+
+    fn with_uci_moves<'a>(
+        mut self,
+        moves: impl Iterator<Item = &'a str>,
+    ) -> Result<Self, ChessError> {
+        for uci in moves {
+            self.make_uci_move(uci)?;
+        }
+        Ok(self)
+    }
+
+    // Executes a move played in the game, remembering the position for repetition detection
+    // and what the move changed for unmake_move
+    pub(crate) fn play_move(&mut self, move_: Move) -> Result<(), String> {
+        let undo = self.undo_for(move_)?;
+        let hash = self.zobrist_hash();
+        self.execute_move(move_)?;
+        self.hash_history.push(hash);
+        self.undo_history.push(undo);
         Ok(())
     }
 
-    fn update_castling_rights_for_move(&mut self, move_: Move) {
-        // Handle pieces moving from critical squares
-        match move_.from() {
-            Position { file: 4, rank: 0 } => {
+    // What playing move_ will change, taken before it is executed
+    fn undo_for(&self, move_: Move) -> Result<Undo, String> {
+        let Some(piece) = self.piece_at_pos(move_.from()) else {
+            return Err("No piece at from position".to_string());
+        };
+        let castling = self.get_castling(move_);
+        let captured = if castling.is_some() {
+            None
+        } else if self.is_move_en_passant(move_) {
+            let captured_pos = Position::new(move_.to().file, move_.from().rank);
+            self.piece_at_pos(captured_pos)
+                .map(|captured| (captured_pos, captured))
+        } else {
+            self.piece_at_pos(move_.to())
+                .map(|captured| (move_.to(), captured))
+        };
+        Ok(Undo {
+            move_,
+            piece,
+            captured,
+            castling,
+            castling_rights: self.castling_rights,
+            en_passant_target: self.en_passant_target,
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+        })
+    }
+
+    // The move before the current position, None if no move was played on this board
+    pub fn last_move(&self) -> Option<Move> {
+        self.undo_history.last().map(|undo| undo.move_)
+    }
+
+    // The piece taken by the last move, if it was a capture
+    pub fn last_captured(&self) -> Option<Piece> {
+        self.undo_history
+            .last()
+            .and_then(|undo| undo.captured)
+            .map(|(_, piece)| piece)
+    }
+
+    // Takes back the last move played on this board, returning it. A pending promotion is
+    // dropped since it belongs to the position being taken back.
+    pub fn unmake_move(&mut self) -> Option<Move> {
+        let undo = self.undo_history.pop()?;
+        self.hash_history.pop();
+        self.promotion_move = None;
+        self.restore(undo)
+    }
+
+    // Puts back what undo recorded, without touching the move history
+    fn restore(&mut self, undo: Undo) -> Option<Move> {
+        let move_ = undo.move_;
+        self.move_turn = match self.move_turn {
+            MoveTurn::White => MoveTurn::Black,
+            MoveTurn::Black => MoveTurn::White,
+        };
+        self.castling_rights = undo.castling_rights;
+        self.en_passant_target = undo.en_passant_target;
+        self.halfmove_clock = undo.halfmove_clock;
+        self.fullmove_number = undo.fullmove_number;
+
+        // Squares are known to be on the board since the move was played
+        if let Some(castling_side) = undo.castling {
+            let rank = move_.from().rank;
+            let is_kingside = matches!(castling_side, CastlingSide::Kingside);
+            let rook_from = Position::new(
                 self.castling_rights
-                    .disable_king_castling(PieceColor::White);
+                    .rook_file(undo.piece.color, is_kingside),
+                rank,
+            );
+            let color = undo.piece.color;
+            let rook = self.piece_at_pos(castling_side.rook_target(color));
+            // Lift both pieces first, as when castling
+            self.set(castling_side.king_target(color), None).ok()?;
+            self.set(castling_side.rook_target(color), None).ok()?;
+            self.set(move_.from(), Some(undo.piece)).ok()?;
+            self.set(rook_from, rook).ok()?;
+        } else {
+            self.set(move_.to(), None).ok()?;
+            self.set(move_.from(), Some(undo.piece)).ok()?;
+            if let Some((captured_pos, captured)) = undo.captured {
+                self.set(captured_pos, Some(captured)).ok()?;
+                if captured.type_ == PieceType::King {
+                    self.set_king(captured.color, Some(captured_pos));
+                }
             }
-            Position { file: 4, rank: 7 } => {
-                self.castling_rights
-                    .disable_king_castling(PieceColor::Black);
+        }
+        if undo.piece.type_ == PieceType::King {
+            self.set_king(undo.piece.color, Some(move_.from()));
+        }
+        Some(move_)
+    }
+
+    fn execute_move(&mut self, move_: Move) -> Result<(), String> {
+        let Some(moving_piece) = self.piece_at_pos(move_.from()) else {
+            return Err("No piece at from position".to_string());
+        };
+
+        let castling = self.get_castling(move_);
+        // Castling is a quiet king move even when the king lands on its own rook in Chess960,
+        // a promotion is a pawn move whether or not it captures
+        let resets_clock = match castling {
+            Some(_) => false,
+            None => {
+                move_.promotion().is_some()
+                    || matches!(moving_piece.type_, PieceType::Pawn)
+                    || self.is_move_capture(move_)
             }
-            Position { file: 0, rank: 0 } => {
-                self.castling_rights
-                    .disable_rook_castling(PieceColor::White, false);
+        };
+
+        if let Some(castling_side) = castling {
+            self.execute_castling(move_, castling_side, moving_piece)?;
+        } else {
+            if self.is_move_en_passant(move_) {
+                let captured_pawn_pos = Position::new(move_.to().file, move_.from().rank);
+                self.set(captured_pawn_pos, None)?;
             }
-            Position { file: 7, rank: 0 } => {
-                self.castling_rights
-                    .disable_rook_castling(PieceColor::White, true);
+            if let Some(captured) = self.piece_at_pos(move_.to())
+                && captured.type_ == PieceType::King
+            {
+                self.set_king(captured.color, None);
             }
-            Position { file: 0, rank: 7 } => {
-                self.castling_rights
-                    .disable_rook_castling(PieceColor::Black, false);
+
+            self.move_piece(move_.from(), move_.to())?;
+            if moving_piece.type_ == PieceType::King {
+                self.set_king(moving_piece.color, Some(move_.to()));
             }
-            Position { file: 7, rank: 7 } => {
-                self.castling_rights
-                    .disable_rook_castling(PieceColor::Black, true);
+
+            if let Some(promotion) = move_.promotion() {
+                let promoted_piece = Piece {
+                    type_: promotion,
+                    color: moving_piece.color,
+                };
+                self.set(move_.to(), Some(promoted_piece))?;
             }
-            _ => {}
         }
 
-        // Handle captures on critical squares
-        // If a rook is captured, the castling_rights are disabled
-        match move_.to() {
-            Position { file: 0, rank: 0 } => {
-                self.castling_rights
-                    .disable_rook_castling(PieceColor::White, false);
-            }
-            Position { file: 7, rank: 0 } => {
-                self.castling_rights
-                    .disable_rook_castling(PieceColor::White, true);
-            }
-            Position { file: 0, rank: 7 } => {
-                self.castling_rights
-                    .disable_rook_castling(PieceColor::Black, false);
-            }
-            Position { file: 7, rank: 7 } => {
-                self.castling_rights
-                    .disable_rook_castling(PieceColor::Black, true);
+        self.update_castling_rights_for_move(move_, moving_piece);
+        self.update_en_passant_target(move_);
+        if resets_clock {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+        if matches!(self.move_turn, MoveTurn::Black) {
+            self.fullmove_number += 1;
+        }
+        self.move_turn = match self.move_turn {
+            MoveTurn::White => MoveTurn::Black,
+            MoveTurn::Black => MoveTurn::White,
+        };
+
+        Ok(())
+    }
+
+    fn execute_castling(
+        &mut self,
+        move_: Move,
+        castling_side: CastlingSide,
+        king: Piece,
+    ) -> Result<(), String> {
+        let rank = move_.from().rank;
+        let is_kingside = matches!(castling_side, CastlingSide::Kingside);
+        let rook_from = Position::new(
+            self.castling_rights.rook_file(king.color, is_kingside),
+            rank,
+        );
+        let rook = self.piece_at_pos(rook_from);
+
+        // Lift both pieces first since the king and rook may land on each other's squares in Chess960
+        self.set(move_.from(), None)?;
+        self.set(rook_from, None)?;
+        self.set(castling_side.king_target(king.color), Some(king))?;
+        self.set(castling_side.rook_target(king.color), rook)?;
+        self.set_king(king.color, Some(castling_side.king_target(king.color)));
+        Ok(())
+    }
+
+    // Passes the turn without moving, for null move pruning in search.
+    // Must never be called while the side to move is in check, since passing would leave the king capturable.
+    pub fn make_null_move(&mut self) {
+        debug_assert!(!self.is_in_check(), "Null move made while in check");
+        self.null_move_en_passant
+            .push(self.en_passant_target.take());
+        self.move_turn = match self.move_turn {
+            MoveTurn::White => MoveTurn::Black,
+            MoveTurn::Black => MoveTurn::White,
+        };
+    }
+
+    pub fn unmake_null_move(&mut self) {
+        let Some(en_passant_target) = self.null_move_en_passant.pop() else {
+            return;
+        };
+        self.en_passant_target = en_passant_target;
+        self.move_turn = match self.move_turn {
+            MoveTurn::White => MoveTurn::Black,
+            MoveTurn::Black => MoveTurn::White,
+        };
+    }
+
+    fn update_castling_rights_for_move(&mut self, move_: Move, moving_piece: Piece) {
+        // Moving the king gives up castling on both sides
+        if let PieceType::King = moving_piece.type_ {
+            self.castling_rights
+                .disable_king_castling(moving_piece.color);
+        }
+
+        // Moving a castling rook, or capturing one, gives up castling on that side
+        for color in [PieceColor::White, PieceColor::Black] {
+            let back_rank = match color {
+                PieceColor::White => 0,
+                PieceColor::Black => BOARD_HEIGHT - 1,
+            };
+            for kingside in [true, false] {
+                let rook_pos =
+                    Position::new(self.castling_rights.rook_file(color, kingside), back_rank);
+                if move_.from() == rook_pos || move_.to() == rook_pos {
+                    self.castling_rights.disable_rook_castling(color, kingside);
+                }
             }
-            _ => {}
         }
     }
 
@@ -786,27 +2545,156 @@ impl Board {
 
     fn set(&mut self, pos: Position, piece: Option<Piece>) -> Result<(), String> {
         let index = pos.to_index()?;
-        self.pieces[index] = piece;
+        let replaced = std::mem::replace(&mut self.pieces[index], piece);
+        for changed in [replaced, piece].into_iter().flatten() {
+            self.pieces_hash ^= zobrist::piece_key(changed, index);
+        }
         Ok(())
     }
 
+    // True when the side to move has legal moves, but all of them are king moves
+    pub fn forced_king_only(&self) -> bool {
+        let mut legal_moves = self.legal_moves_iter().peekable();
+        legal_moves.peek().is_some()
+            && legal_moves.all(|move_| {
+                self.piece_at_pos(move_.from())
+                    .is_some_and(|piece| piece.type_ == PieceType::King)
+            })
+    }
+
     pub fn is_checkmate(&self) -> bool {
-        self.is_in_check() && self.all_legal_moves().is_empty()
+        self.is_in_check() && self.legal_moves_iter().next().is_none()
     }
 
     pub fn is_stalemate(&self) -> bool {
-        !self.is_in_check() && self.all_legal_moves().is_empty()
+        !self.is_in_check() && self.legal_moves_iter().next().is_none()
+    }
+
+    // No checkmate is possible: bare kings, a single knight, or any bishops that all stand on
+    // squares of one color. Two knights or bishops on both colors can still mate, so they count.
+    pub fn is_insufficient_material(&self) -> bool {
+        let mut knights = 0;
+        let mut light_bishops = 0;
+        let mut dark_bishops = 0;
+        for (pos, piece) in self.iter_pieces() {
+            match piece.type_ {
+                PieceType::King => {}
+                PieceType::Knight => knights += 1,
+                PieceType::Bishop if pos.is_light() => light_bishops += 1,
+                PieceType::Bishop => dark_bishops += 1,
+                PieceType::Pawn | PieceType::Rook | PieceType::Queen => return false,
+            }
+        }
+        match knights {
+            0 => light_bishops == 0 || dark_bishops == 0,
+            1 => light_bishops == 0 && dark_bishops == 0,
+            _ => false,
+        }
+    }
+
+    pub fn status(&self) -> GameStatus {
+        self.terminal_state().unwrap_or(GameStatus::Ongoing)
+    }
+
+    // The way the game ended, or None while it's still going. Legal moves are only
+    // generated until the first one is found.
+    pub fn terminal_state(&self) -> Option<GameStatus> {
+        if self.legal_moves_iter().next().is_none() {
+            return Some(if self.is_in_check() {
+                GameStatus::Checkmate
+            } else {
+                GameStatus::Stalemate
+            });
+        }
+        if self.is_insufficient_material() {
+            return Some(GameStatus::InsufficientMaterial);
+        }
+        if self.repetition_count() >= 5 {
+            return Some(GameStatus::FivefoldRepetition);
+        }
+        if self.halfmove_clock >= 150 {
+            return Some(GameStatus::SeventyFiveMove);
+        }
+        None
     }
 
-    fn is_promotion_move(&self, move_: Move) -> bool {
+    // Number of times the current position has occurred, counting itself
+    pub fn repetition_count(&self) -> usize {
+        let hash = self.zobrist_hash();
+        1 + self
+            .hash_history
+            .iter()
+            .filter(|&&previous| previous == hash)
+            .count()
+    }
+
+    // Whether the current position already occurred within the last ply_limit plies. Positions
+    // before the last capture or pawn move can't come back, so the search stops there.
+    pub fn is_repetition_in_history(&self, ply_limit: usize) -> bool {
+        let hash = self.zobrist_hash();
+        let reversible_plies = (self.halfmove_clock as usize).min(ply_limit);
+        self.hash_history
+            .iter()
+            .rev()
+            .take(reversible_plies)
+            .any(|&previous| previous == hash)
+    }
+
+    // Automatic draws are reported before the ones a player has to claim
+    pub fn draw_reason(&self) -> Option<DrawReason> {
+        if self.legal_moves_iter().next().is_none() {
+            return if self.is_in_check() {
+                None
+            } else {
+                Some(DrawReason::Stalemate)
+            };
+        }
+        if self.is_insufficient_material() {
+            return Some(DrawReason::InsufficientMaterial);
+        }
+
+        let repetitions = self.repetition_count();
+        if repetitions >= 5 {
+            Some(DrawReason::FivefoldRepetition)
+        } else if self.halfmove_clock >= 150 {
+            Some(DrawReason::SeventyFiveMove)
+        } else if repetitions >= 3 {
+            Some(DrawReason::ThreefoldRepetition)
+        } else if self.halfmove_clock >= 100 {
+            Some(DrawReason::FiftyMove)
+        } else {
+            None
+        }
+    }
+
+    // Threefold repetition and the fifty-move rule end the game only when a player claims them
+    pub fn can_claim_draw(&self) -> bool {
+        self.repetition_count() >= 3 || self.halfmove_clock >= 100
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        self.status() != GameStatus::Ongoing
+    }
+
+    pub fn winner(&self) -> Option<PieceColor> {
+        if !self.is_checkmate() {
+            return None;
+        }
+        // The side to move is the one that got mated
+        match self.move_turn {
+            MoveTurn::White => Some(PieceColor::Black),
+            MoveTurn::Black => Some(PieceColor::White),
+        }
+    }
+
+    pub(crate) fn is_promotion_move(&self, move_: Move) -> bool {
         let Some(moving_piece) = self.piece_at_pos(move_.from()) else {
             return false;
         };
         if !matches!(moving_piece.type_, PieceType::Pawn) {
             return false;
         }
-        let destination_rank = move_.to().rank;
-        destination_rank == 0 || destination_rank == 7
+        move_.to().rank == moving_piece.color.promotion_rank()
     }
 
     pub fn resolve_promotion(&mut self, piece_type: PieceType) -> Result<(), String> {
@@ -819,18 +2707,7 @@ impl Board {
             _ => return Err("Invalid promotion piece".to_string()),
         }
 
-        self.execute_move(move_)?;
-
-        let Some(pawn_piece) = self.piece_at_pos(move_.to()) else {
-            return Err("No piece at promotion square".to_string());
-        };
-
-        let promoted_piece = Piece {
-            type_: piece_type,
-            color: pawn_piece.color,
-        };
-
-        self.set(move_.to(), Some(promoted_piece))?;
+        self.play_move(Move::new_promotion(move_.from(), move_.to(), piece_type))?;
         self.promotion_move = None;
 
         Ok(())
@@ -841,11 +2718,31 @@ impl Board {
     }
 }
 
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_string_from(PieceColor::White))
+    }
+}
+
+impl Index<Position> for Board {
+    type Output = Option<Piece>;
+
+    // Panics on positions off the board, like slice indexing
+    fn index(&self, pos: Position) -> &Self::Output {
+        let index = pos.to_index().expect("Position is not on board");
+        &self.pieces[index]
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
-        board::{Board, MoveResult, Position},
-        piece::{Move, Piece, PieceColor, PieceType},
+        board::{
+            BOARD_BYTES, Board, CastlingRights, CastlingSide, DrawReason, GameStatus, MoveResult,
+            MoveStats, MoveTurn, Position, fullmove_from_ply,
+        },
+        error::{CastleError, ChessError},
+        piece::{Move, Offset, Piece, PieceColor, PieceType},
     };
 
     #[test]
@@ -936,6 +2833,157 @@ mod tests {
         assert!(!board3.move_legal(queenside_castle));
     }
 
+    #[test]
+    fn test_pawn_move_generation() {
+        // Both pawns are blocked, and there is nothing to capture on the diagonals
+        let board = Board::from_fen("4k3/8/8/4p3/4P3/1n6/1P6/4K3 w - - 0 1").unwrap();
+        assert!(board.legal_moves(Position::new(4, 3)).is_empty());
+        assert!(board.legal_moves(Position::new(1, 1)).is_empty());
+        assert!(
+            board.all_legal_moves().iter().all(|move_| board
+                .piece_at_pos(move_.from())
+                .unwrap()
+                .type_
+                == PieceType::King)
+        );
+
+        // Only the en passant square counts as an empty capture square
+        let board = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+        let mut moves = board.legal_moves(Position::new(4, 4));
+        moves.sort_by_key(|pos| pos.file);
+        assert_eq!(moves, vec![Position::new(3, 5), Position::new(4, 5)]);
+        let board = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            board.legal_moves(Position::new(4, 4)),
+            vec![Position::new(4, 5)]
+        );
+
+        // Black pushes are blocked the same way, even by a piece on the double push square
+        let board = Board::from_fen("4k3/3p3p/3B4/7N/8/8/8/4K3 b - - 0 1").unwrap();
+        assert!(board.legal_moves(Position::new(3, 6)).is_empty());
+        assert_eq!(
+            board.legal_moves(Position::new(7, 6)),
+            vec![Position::new(7, 5)]
+        );
+    }
+
+    #[test]
+    fn test_castle_legality() {
+        let legality = |fen: &str, side| Board::from_fen(fen).unwrap().castle_legality(side);
+        let (kingside, queenside) = (CastlingSide::Kingside, CastlingSide::Queenside);
+
+        let fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+        assert_eq!(legality(fen, kingside), Ok(()));
+        assert_eq!(legality(fen, queenside), Ok(()));
+        let fen = "r3k2r/8/8/8/8/8/8/R3K2R b KQk - 0 1";
+        assert_eq!(legality(fen, kingside), Ok(()));
+        assert_eq!(legality(fen, queenside), Err(CastleError::NoRights));
+
+        let mut board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K3 w Q - 0 1").unwrap();
+        board.set_castling_rights(CastlingRights::from_flags(true, true, false, false));
+        assert_eq!(
+            board.castle_legality(kingside),
+            Err(CastleError::RookMissing)
+        );
+        let fen = "r3k2r/8/8/8/8/8/8/RN2K2R w KQkq - 0 1";
+        assert_eq!(legality(fen, queenside), Err(CastleError::PathBlocked));
+
+        let fen = "4r1k1/8/8/8/8/8/8/R3K2R w KQ - 0 1";
+        assert_eq!(legality(fen, kingside), Err(CastleError::InCheck));
+        let fen = "5rk1/8/8/8/8/8/8/R3K2R w KQ - 0 1";
+        assert_eq!(legality(fen, kingside), Err(CastleError::ThroughCheck));
+        assert_eq!(legality(fen, queenside), Ok(()));
+        let fen = "6rk/8/8/8/8/8/8/R3K2R w KQ - 0 1";
+        assert_eq!(legality(fen, kingside), Err(CastleError::IntoCheck));
+
+        // Only the king's squares have to be safe, the rook may cross an attacked b1
+        let fen = "1r2k3/8/8/8/8/8/8/R3K2R w KQ - 0 1";
+        assert_eq!(legality(fen, queenside), Ok(()));
+        assert!(
+            Board::from_fen(fen)
+                .unwrap()
+                .move_legal(Move::from_uci("e1c1").unwrap())
+        );
+
+        // No king on the back rank means no castling at all
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/4K3/R6R w - - 0 1").unwrap();
+        board.set_castling_rights(CastlingRights::from_flags(true, true, false, false));
+        assert_eq!(board.castle_legality(kingside), Err(CastleError::NoRights));
+    }
+
+    #[test]
+    fn test_king_two_squares_without_castling() {
+        // No castling rights, so sliding the king two squares is never legal
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K2R w - - 0 1").unwrap();
+        for uci in ["e1g1", "e1c1", "e1e3"] {
+            assert!(!board.move_legal(Move::from_uci(uci).unwrap()));
+        }
+        assert!(
+            !board
+                .legal_moves(Position::new(4, 0))
+                .contains(&Position::new(6, 0))
+        );
+
+        // In Chess960 castling goes onto the rook, a two square king move is just illegal
+        let board = Board::new_chess960("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+        assert!(!board.move_legal(Move::from_uci("e1g1").unwrap()));
+        assert!(!board.move_legal(Move::from_uci("e1c1").unwrap()));
+        assert!(board.move_legal(Move::from_uci("e1h1").unwrap()));
+    }
+
+    #[test]
+    fn test_chess960_castling() {
+        // King on b1 with rooks on a1 and h1, castling is king takes own rook
+        let mut board = Board::new_chess960("6kr/8/8/8/8/8/8/RK5R w KQk - 0 1").unwrap();
+        let queenside_castle = Move::new(Position::new(1, 0), Position::new(0, 0));
+        let kingside_castle = Move::new(Position::new(1, 0), Position::new(7, 0));
+        assert!(board.move_legal(queenside_castle));
+        assert!(board.move_legal(kingside_castle));
+
+        let result = board.make_move(kingside_castle.from(), kingside_castle.to());
+        assert_eq!(result, MoveResult::Normal);
+        let king_at_g1 = board.piece_at_pos(Position::new(6, 0)).unwrap();
+        assert_eq!(king_at_g1.type_, PieceType::King);
+        let rook_at_f1 = board.piece_at_pos(Position::new(5, 0)).unwrap();
+        assert_eq!(rook_at_f1.type_, PieceType::Rook);
+        assert!(board.piece_at_pos(Position::new(7, 0)).is_none());
+        assert!(board.piece_at_pos(Position::new(1, 0)).is_none());
+
+        // Black king on g8 and rook on h8, f8 is empty so kingside castling only moves the rook
+        let result = board.make_move(Position::new(6, 7), Position::new(7, 7));
+        assert_eq!(result, MoveResult::Normal);
+        let king_at_g8 = board.piece_at_pos(Position::new(6, 7)).unwrap();
+        assert_eq!(king_at_g8.type_, PieceType::King);
+        let rook_at_f8 = board.piece_at_pos(Position::new(5, 7)).unwrap();
+        assert_eq!(rook_at_f8.type_, PieceType::Rook);
+
+        // A piece between the king and its target square blocks castling
+        let board = Board::new_chess960("8/8/8/8/8/8/8/1RK1B1R1 w KQ - 0 1").unwrap();
+        assert!(!board.move_legal(Move::new(Position::new(2, 0), Position::new(6, 0))));
+        assert!(board.move_legal(Move::new(Position::new(2, 0), Position::new(1, 0))));
+    }
+
+    #[test]
+    fn test_shredder_fen_castling() {
+        // File letters map onto the same rights as KQkq in a standard position
+        let board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w HAha - 0 1").unwrap();
+        let kingside_castle = Move::new(Position::new(4, 0), Position::new(6, 0));
+        let queenside_castle = Move::new(Position::new(4, 0), Position::new(2, 0));
+        assert!(board.move_legal(kingside_castle));
+        assert!(board.move_legal(queenside_castle));
+
+        // Only the rook on g1 may castle, the one on h1 isn't a castling rook
+        let board = Board::new_chess960("4k3/8/8/8/8/8/8/1RK3RR w G - 0 1").unwrap();
+        assert!(!board.move_legal(Move::new(Position::new(2, 0), Position::new(7, 0))));
+        assert!(board.move_legal(Move::new(Position::new(2, 0), Position::new(6, 0))));
+        assert!(!board.move_legal(Move::new(Position::new(2, 0), Position::new(1, 0))));
+
+        assert!(Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KAha - 0 1").is_err());
+        assert!(Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KX - 0 1").is_err());
+        assert!(Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KK - 0 1").is_err());
+        assert!(Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w E - 0 1").is_err());
+    }
+
     #[test]
     fn test_en_passant() {
         // White pawn on e5, black pawn on f7
@@ -955,11 +3003,1145 @@ mod tests {
     }
 
     #[test]
-    fn test_checkmate() {
-        // Black king on g8, white king on g6, white rook on a8
-        let board = Board::from_fen("R5k1/8/6K1/8/8/8/8/8 b - - 0 1").unwrap();
-        assert!(board.is_checkmate());
-    }
+    fn test_double_check() {
+        // White king on e1, black rook on e8 and black knight on d3 both give check
+        let board = Board::from_fen("4r3/8/8/8/8/3n4/8/4K3 w - - 0 1").unwrap();
+        assert!(board.is_double_check());
+        let mut attackers = board.attackers_of(Position::new(4, 0), PieceColor::Black);
+        attackers.sort_by_key(|pos| (pos.file, pos.rank));
+        assert_eq!(attackers, vec![Position::new(3, 2), Position::new(4, 7)]);
+
+        // Single check from the rook only
+        let board = Board::from_fen("4r3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert!(board.is_in_check());
+        assert!(!board.is_double_check());
+    }
+
+    #[test]
+    fn test_strict_en_passant() {
+        // Black just played d7-d5 next to the white pawn on e5
+        let fen = "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1";
+        assert!(Board::from_fen_strict(fen).is_ok());
+
+        // No white pawn can capture on d6
+        assert!(Board::from_fen_strict("4k3/8/8/3p4/8/8/8/4K3 w - d6 0 1").is_err());
+        // No black pawn on d5 that could have made the double push
+        assert!(Board::from_fen_strict("4k3/8/8/4P3/8/8/8/4K3 w - d6 0 1").is_err());
+        // Target on the wrong rank for the side to move
+        assert!(Board::from_fen_strict("4k3/8/8/8/3Pp3/8/8/4K3 w - d3 0 1").is_err());
+        // The lenient parser still accepts it
+        assert!(Board::from_fen("4k3/8/8/3p4/8/8/8/4K3 w - d6 0 1").is_ok());
+    }
+
+    #[test]
+    fn test_flipped() {
+        // Black just played d7-d5, leaving an en passant target on d6
+        let board = Board::from_fen("4k2r/8/8/3p4/4P3/8/8/R3K3 w Qk d6 0 1").unwrap();
+        let flipped = board.flipped();
+
+        // Pieces are mirrored vertically and change color
+        let pawn = flipped.piece_at_pos(Position::new(4, 4)).unwrap();
+        assert_eq!(
+            (pawn.type_, pawn.color),
+            (PieceType::Pawn, PieceColor::Black)
+        );
+        let pawn = flipped.piece_at_pos(Position::new(3, 3)).unwrap();
+        assert_eq!(
+            (pawn.type_, pawn.color),
+            (PieceType::Pawn, PieceColor::White)
+        );
+        let king = flipped.piece_at_pos(Position::new(4, 0)).unwrap();
+        assert_eq!(
+            (king.type_, king.color),
+            (PieceType::King, PieceColor::White)
+        );
+
+        // Black is to move and the en passant target moves from d6 to d3
+        assert!(matches!(flipped.move_turn, MoveTurn::Black));
+        assert_eq!(flipped.en_passant_target, Some(Position::new(3, 2)));
+
+        // Castling rights swap sides
+        assert!(flipped.castling_rights.can_castle(PieceColor::White, true));
+        assert!(!flipped.castling_rights.can_castle(PieceColor::White, false));
+        assert!(flipped.castling_rights.can_castle(PieceColor::Black, false));
+        assert!(!flipped.castling_rights.can_castle(PieceColor::Black, true));
+
+        assert_eq!(
+            board.all_legal_moves().len(),
+            flipped.all_legal_moves().len()
+        );
+
+        // Flipping twice gives back the original position
+        let twice = flipped.flipped();
+        assert_eq!(twice.en_passant_target, board.en_passant_target);
+        for index in 0..64 {
+            let pos = Position::from_index(index);
+            let original = board
+                .piece_at_pos(pos)
+                .map(|piece| (piece.type_, piece.color));
+            let restored = twice
+                .piece_at_pos(pos)
+                .map(|piece| (piece.type_, piece.color));
+            assert_eq!(original, restored);
+        }
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_random_move() {
+        use rand::SeedableRng;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+        let board = Board::starting_position();
+        let move_ = board.random_move(&mut rng).unwrap();
+        assert!(board.move_legal(move_));
+
+        // No moves to pick from when the game is over
+        let board = Board::from_fen("R5k1/8/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert!(board.random_move(&mut rng).is_none());
+    }
+
+    #[test]
+    fn test_zobrist_hash() {
+        // The same position reached by different move orders hashes the same
+        let mut board = Board::starting_position();
+        board.make_move(Position::new(6, 0), Position::new(5, 2));
+        board.make_move(Position::new(6, 7), Position::new(5, 5));
+        board.make_move(Position::new(1, 0), Position::new(2, 2));
+        let mut transposed = Board::starting_position();
+        transposed.make_move(Position::new(1, 0), Position::new(2, 2));
+        transposed.make_move(Position::new(6, 7), Position::new(5, 5));
+        transposed.make_move(Position::new(6, 0), Position::new(5, 2));
+        assert_eq!(board.zobrist_hash(), transposed.zobrist_hash());
+
+        // Side to move and castling rights are part of the hash
+        let white = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        let black = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1").unwrap();
+        let no_castling = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w - - 0 1").unwrap();
+        assert_ne!(white.zobrist_hash(), black.zobrist_hash());
+        assert_ne!(white.zobrist_hash(), no_castling.zobrist_hash());
+
+        // An en passant square that can't be used doesn't change the hash
+        let with_target = Board::from_fen("4k3/8/8/3p4/8/8/8/4K3 w - d6 0 1").unwrap();
+        let without_target = Board::from_fen("4k3/8/8/3p4/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(with_target.zobrist_hash(), without_target.zobrist_hash());
+
+        // The incremental hash matches a fresh board through captures, en passant, castling
+        // and promotion, and after taking the moves back
+        let mut board = Board::from_fen("r3k2r/1P6/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1").unwrap();
+        let start_hash = board.zobrist_hash();
+        for uci in ["e5d6", "e8g8", "e1c1", "a8a2", "b7b8q"] {
+            board
+                .make_move_checked(Move::from_uci(uci).unwrap())
+                .unwrap();
+            let fresh = Board::from_fen(&board.to_fen()).unwrap();
+            assert_eq!(board.zobrist_hash(), fresh.zobrist_hash(), "after {}", uci);
+        }
+        while board.unmake_move().is_some() {}
+        assert_eq!(board.zobrist_hash(), start_hash);
+    }
+
+    #[test]
+    fn test_legal_moves_in_place() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "4k3/8/8/2KpP2r/8/8/8/8 w - d6 0 1",
+        ] {
+            let mut board = Board::from_fen(fen).unwrap();
+            let expected = board.all_legal_moves();
+            assert_eq!(board.legal_moves_in_place(), expected);
+            // Every tried move was taken back
+            assert_eq!(board.to_fen(), Board::from_fen(fen).unwrap().to_fen());
+            assert_eq!(
+                board.zobrist_hash(),
+                Board::from_fen(fen).unwrap().zobrist_hash()
+            );
+            assert!(board.last_move().is_none());
+        }
+    }
+
+    #[test]
+    fn test_count_legal_moves() {
+        let board = Board::starting_position();
+        assert_eq!(board.count_legal_moves(), 20);
+
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1",
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            assert_eq!(board.count_legal_moves(), board.all_legal_moves().len());
+        }
+
+        // A promotion is listed once per piece, so a full move can be looked up directly
+        let board = Board::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let a7 = Position::new(0, 6);
+        let a8 = Position::new(0, 7);
+        let moves = board.all_legal_moves();
+        assert!(moves.contains(&Move::new_promotion(a7, a8, PieceType::Queen)));
+        assert!(moves.contains(&Move::new_promotion(a7, a8, PieceType::Knight)));
+        assert!(!moves.contains(&Move::new(a7, a8)));
+        assert_eq!(board.count_legal_moves(), 9);
+    }
+
+    #[test]
+    fn test_legal_moves_from() {
+        let board = Board::from_fen("1n2k3/P7/8/8/8/8/8/R3K3 w Q - 0 1").unwrap();
+        let pawn_moves = board.legal_moves_from(Position::new(0, 6));
+        assert_eq!(pawn_moves.len(), 8);
+        assert!(pawn_moves.contains(&Move::new_promotion(
+            Position::new(0, 6),
+            Position::new(1, 7),
+            PieceType::Knight
+        )));
+        assert!(pawn_moves.iter().all(|move_| board.move_legal(*move_)));
+
+        let king_moves = board.legal_moves_from(Position::new(4, 0));
+        assert!(king_moves.contains(&Move::new(Position::new(4, 0), Position::new(2, 0))));
+        assert_eq!(king_moves.len(), 6);
+
+        // Empty squares and the opponent's pieces have no moves
+        assert!(board.legal_moves_from(Position::new(4, 4)).is_empty());
+        assert!(board.legal_moves_from(Position::new(1, 7)).is_empty());
+    }
+
+    #[test]
+    fn test_legal_moves_grouped() {
+        let board = Board::starting_position();
+        let grouped = board.legal_moves_grouped();
+        // Eight pawns and two knights can move, the rest of the back rank is blocked
+        assert_eq!(grouped.len(), 10);
+        assert!(grouped.iter().all(|(_, moves)| moves.len() == 2));
+        assert!(
+            grouped
+                .iter()
+                .all(|(pos, moves)| moves.iter().all(|move_| move_.from() == *pos))
+        );
+
+        let board = Board::from_fen("1n2k3/P7/8/8/8/8/8/R3K3 w Q - 0 1").unwrap();
+        let grouped = board.legal_moves_grouped();
+        let total: usize = grouped.iter().map(|(_, moves)| moves.len()).sum();
+        assert_eq!(total, board.all_legal_moves().len());
+        let (_, pawn_moves) = grouped
+            .iter()
+            .find(|(pos, _)| *pos == Position::new(0, 6))
+            .unwrap();
+        assert_eq!(pawn_moves, &board.legal_moves_from(Position::new(0, 6)));
+    }
+
+    #[test]
+    fn test_gives_check() {
+        let board = Board::from_fen("4k3/P7/8/8/8/8/8/R3K1N1 w - - 0 1").unwrap();
+        // Promoting to a queen checks along the back rank, a knight doesn't
+        assert!(board.gives_check(Move::from_uci("a7a8").unwrap()));
+        assert!(board.gives_check(Move::from_uci("a7a8q").unwrap()));
+        assert!(!board.gives_check(Move::from_uci("a7a8n").unwrap()));
+        assert!(!board.gives_check(Move::from_uci("g1f3").unwrap()));
+        // Illegal moves never give check
+        assert!(!board.gives_check(Move::from_uci("a1e8").unwrap()));
+
+        // Moving the knight uncovers the rook
+        let board = Board::from_fen("4k3/8/8/8/4N3/8/8/4R1K1 w - - 0 1").unwrap();
+        assert!(board.gives_check(Move::from_uci("e4c3").unwrap()));
+    }
+
+    #[test]
+    fn test_move_stats() {
+        assert_eq!(
+            Board::starting_position().move_stats(),
+            MoveStats {
+                total: 20,
+                captures: 0,
+                checks: 0,
+                promotions: 0
+            }
+        );
+
+        let board = Board::from_fen("1n2k3/P7/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        let stats = board.move_stats();
+        assert_eq!(stats.total, board.all_legal_moves().len());
+        // Every promotion piece counts as its own move
+        assert_eq!(stats.captures, 4);
+        assert_eq!(stats.promotions, 8);
+        // The knight on b8 shields the king from a8=Q, so only axb8=Q and axb8=R check
+        assert_eq!(stats.checks, 2);
+    }
+
+    #[test]
+    fn test_legal_moves_iter() {
+        let board = Board::starting_position();
+        assert_eq!(
+            board.legal_moves_iter().collect::<Vec<Move>>(),
+            board.all_legal_moves()
+        );
+        assert!(
+            board
+                .legal_moves_iter()
+                .all(|move_| board.move_legal(move_))
+        );
+
+        let board = Board::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1").unwrap();
+        assert!(board.legal_moves_iter().next().is_none());
+    }
+
+    #[test]
+    fn test_mobility() {
+        let board = Board::starting_position();
+        assert_eq!(board.mobility(PieceColor::White), 20);
+        assert_eq!(board.mobility(PieceColor::Black), 20);
+
+        // The pinned rook can only move along the e-file
+        let board = Board::from_fen("4r3/8/8/8/4R3/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(board.mobility(PieceColor::White), 11);
+        assert_eq!(board.mobility(PieceColor::Black), 11);
+    }
+
+    #[test]
+    fn test_unmake_move() {
+        let fens = [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 3 10",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "1n2k3/P7/8/8/8/8/7p/4K1N1 b - - 0 40",
+        ];
+        for fen in fens {
+            let mut board = Board::from_fen(fen).unwrap();
+            let hash = board.zobrist_hash();
+            for move_ in board.all_legal_moves() {
+                let move_ = if board.is_promotion_move(move_) {
+                    Move::new_promotion(move_.from(), move_.to(), PieceType::Knight)
+                } else {
+                    move_
+                };
+                board.make_move_checked(move_).unwrap();
+                assert_eq!(board.unmake_move(), Some(move_));
+                assert_eq!(board.to_fen(), fen);
+                assert_eq!(board.zobrist_hash(), hash);
+                assert_eq!(board.repetition_count(), 1);
+            }
+        }
+
+        // Chess960 castling where the king is already on its target square
+        let fen = "4k3/8/8/8/8/8/8/6KR w H - 0 1";
+        let mut board = Board::new_chess960(fen).unwrap();
+        board.make_uci_move("g1h1").unwrap();
+        assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/8/5RK1 b - - 1 1");
+        board.unmake_move();
+        assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/8/6KR w K - 0 1");
+        assert_eq!(
+            board.castling_rights(),
+            Board::new_chess960(fen).unwrap().castling_rights()
+        );
+
+        // Nothing to take back, and a pending promotion is dropped with its move
+        let mut board = Board::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(board.unmake_move(), None);
+        board.make_uci_move("e1d1").unwrap();
+        board.make_uci_move("e8f7").unwrap();
+        assert_eq!(
+            board.make_move(Position::new(0, 6), Position::new(0, 7)),
+            MoveResult::Promotion
+        );
+        assert_eq!(board.unmake_move(), Some(Move::from_uci("e8f7").unwrap()));
+        assert!(board.resolve_promotion(PieceType::Queen).is_err());
+        assert_eq!(board.turn(), PieceColor::Black);
+    }
+
+    #[test]
+    fn test_last_move() {
+        let mut board = Board::from_fen("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(board.last_move(), None);
+
+        board.make_uci_move("e4d5").unwrap();
+        assert_eq!(board.last_move(), Some(Move::from_uci("e4d5").unwrap()));
+        assert_eq!(
+            board.last_captured(),
+            Some(Piece {
+                type_: PieceType::Pawn,
+                color: PieceColor::Black
+            })
+        );
+
+        board.make_uci_move("e8d7").unwrap();
+        assert_eq!(board.last_move(), Some(Move::from_uci("e8d7").unwrap()));
+        assert_eq!(board.last_captured(), None);
+
+        board.unmake_move();
+        assert_eq!(board.last_move(), Some(Move::from_uci("e4d5").unwrap()));
+    }
+
+    #[test]
+    fn test_legal_moves_for() {
+        let board = Board::starting_position();
+        assert_eq!(
+            board.legal_moves_for(PieceColor::White),
+            board.all_legal_moves()
+        );
+        assert_eq!(board.legal_moves_for(PieceColor::Black).len(), 20);
+
+        // The rook on d1 keeps the black king off the d-file even though it's white's turn
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/3RK3 w - - 0 1").unwrap();
+        let mut targets: Vec<String> = board
+            .legal_moves_for(PieceColor::Black)
+            .iter()
+            .map(|move_| move_.to().to_algebraic())
+            .collect();
+        targets.sort();
+        assert_eq!(targets, vec!["e7", "f7", "f8"]);
+        assert_eq!(board.turn(), PieceColor::White);
+    }
+
+    #[test]
+    fn test_is_empty() {
+        let board = Board::starting_position();
+        assert!(board.is_occupied(Position::new(4, 0)));
+        assert!(!board.is_empty(Position::new(4, 0)));
+        assert!(board.is_empty(Position::new(4, 3)));
+        assert!(!board.is_occupied(Position::new(4, 3)));
+        assert!(board.is_empty(Position::new(8, 0)));
+    }
+
+    #[test]
+    fn test_index() {
+        let board = Board::starting_position();
+        let king = board[Position::new(4, 0)].unwrap();
+        assert_eq!(
+            (king.type_, king.color),
+            (PieceType::King, PieceColor::White)
+        );
+        assert!(board[Position::new(4, 3)].is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_index_off_board() {
+        let board = Board::starting_position();
+        let _ = board[Position::new(8, 0)];
+    }
+
+    #[test]
+    fn test_iter_pieces() {
+        let board = Board::starting_position();
+        assert_eq!(board.iter_pieces().count(), 32);
+
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1").unwrap();
+        let pieces: Vec<(Position, PieceType, PieceColor)> = board
+            .iter_pieces()
+            .map(|(pos, piece)| (pos, piece.type_, piece.color))
+            .collect();
+        assert_eq!(
+            pieces,
+            vec![
+                (Position::new(0, 0), PieceType::Rook, PieceColor::White),
+                (Position::new(4, 0), PieceType::King, PieceColor::White),
+                (Position::new(4, 7), PieceType::King, PieceColor::Black),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_piece_list() {
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1").unwrap();
+        let snapshot = board.piece_list();
+        assert_eq!(snapshot, board.iter_pieces().collect::<Vec<_>>());
+
+        // The snapshot keeps the position it was taken from
+        board.make_uci_move("a1a8").unwrap();
+        assert_eq!(snapshot.len(), 3);
+        assert_eq!(snapshot[0].0, Position::new(0, 0));
+        assert_eq!(board.piece_list()[1].0, Position::new(0, 7));
+    }
+
+    #[test]
+    fn test_squares() {
+        let squares: Vec<Position> = Board::squares().collect();
+        assert_eq!(squares.len(), 64);
+        assert_eq!(squares[0], Position::new(0, 0));
+        assert_eq!(squares[1], Position::new(1, 0));
+        assert_eq!(squares[8], Position::new(0, 1));
+        assert_eq!(squares[63], Position::new(7, 7));
+        assert!(squares.iter().all(|pos| pos.is_on_board()));
+    }
+
+    #[test]
+    fn test_find_pieces() {
+        let board = Board::starting_position();
+        assert_eq!(
+            board.find_pieces(PieceType::Knight, PieceColor::Black),
+            vec![Position::new(1, 7), Position::new(6, 7)]
+        );
+        assert_eq!(
+            board.find_pieces(PieceType::Pawn, PieceColor::White).len(),
+            8
+        );
+
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert!(
+            board
+                .find_pieces(PieceType::Queen, PieceColor::White)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_position_algebraic() {
+        assert_eq!(Position::new(4, 3).to_algebraic(), "e4");
+        assert_eq!(Position::new(0, 0).to_algebraic(), "a1");
+        assert_eq!(Position::new(7, 7).to_algebraic(), "h8");
+        assert_eq!(Position::new(2, 5).file_char(), 'c');
+        assert_eq!(Position::new(2, 5).rank_char(), '6');
+        assert_eq!(Position::new(-1, 0).to_algebraic(), "??");
+
+        assert_eq!(Position::from_algebraic("e4"), Ok(Position::new(4, 3)));
+        assert_eq!(Position::from_algebraic("h8"), Ok(Position::new(7, 7)));
+        assert!(Position::from_algebraic("i1").is_err());
+        assert!(Position::from_algebraic("a9").is_err());
+        assert!(Position::from_algebraic("e44").is_err());
+        assert!(Position::from_algebraic("").is_err());
+    }
+
+    #[test]
+    fn test_position_neighbors() {
+        assert_eq!(Position::new(4, 3).neighbors().count(), 8);
+        assert_eq!(Position::new(0, 4).neighbors().count(), 5);
+
+        let mut corner: Vec<Position> = Position::new(7, 7).neighbors().collect();
+        corner.sort_by_key(|pos| (pos.file, pos.rank));
+        assert_eq!(
+            corner,
+            vec![
+                Position::new(6, 6),
+                Position::new(6, 7),
+                Position::new(7, 6)
+            ]
+        );
+        assert!(
+            Position::new(4, 3)
+                .neighbors()
+                .all(|pos| pos.chebyshev_distance(Position::new(4, 3)) == 1)
+        );
+    }
+
+    #[test]
+    fn test_knight_targets() {
+        assert_eq!(Position::new(4, 3).knight_targets().len(), 8);
+        assert_eq!(Position::new(1, 0).knight_targets().len(), 3);
+
+        let mut corner = Position::new(0, 0).knight_targets();
+        corner.sort_by_key(|pos| pos.file);
+        assert_eq!(corner, vec![Position::new(1, 2), Position::new(2, 1)]);
+    }
+
+    #[test]
+    fn test_position_offset() {
+        let e2 = Position::new(4, 1);
+        let e4 = Position::new(4, 3);
+        let b5 = Position::new(1, 4);
+        assert_eq!(e2.offset_to(e4), Offset::new(0, 2));
+        assert_eq!(e4 - e2, Offset::new(0, 2));
+        assert_eq!(e4.offset_to(b5), Offset::new(-3, 1));
+        assert_eq!(e4 + e4.offset_to(b5), b5);
+        assert_eq!(e4.offset_to(e4), Offset::new(0, 0));
+    }
+
+    #[test]
+    fn test_position_distance() {
+        let a1 = Position::new(0, 0);
+        let h8 = Position::new(7, 7);
+        let e4 = Position::new(4, 3);
+        assert_eq!(a1.chebyshev_distance(h8), 7);
+        assert_eq!(a1.manhattan_distance(h8), 14);
+        assert_eq!(e4.chebyshev_distance(Position::new(2, 4)), 2);
+        assert_eq!(e4.manhattan_distance(Position::new(2, 4)), 3);
+        assert_eq!(e4.chebyshev_distance(e4), 0);
+        assert_eq!(h8.manhattan_distance(e4), e4.manhattan_distance(h8));
+    }
+
+    #[test]
+    fn test_square_color() {
+        assert!(Position::new(0, 0).is_dark());
+        assert!(Position::new(7, 7).is_dark());
+        assert!(Position::new(7, 0).is_light());
+        assert!(Position::new(3, 0).is_light());
+        assert!(Position::new(4, 0).is_dark());
+        assert!(!Position::new(4, 0).is_light());
+    }
+
+    #[test]
+    fn test_legal_moves_contains() {
+        let board = Board::starting_position();
+        let legal_moves = board.all_legal_moves();
+        assert!(legal_moves.contains(&Move::new(Position::new(4, 1), Position::new(4, 3))));
+        assert!(!legal_moves.contains(&Move::new(Position::new(4, 1), Position::new(4, 4))));
+    }
+
+    #[test]
+    fn test_display() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/P7/R3K3 w - - 0 1").unwrap();
+        let white_view = [
+            "8 . . . . k . . .",
+            "7 . . . . . . . .",
+            "6 . . . . . . . .",
+            "5 . . . . . . . .",
+            "4 . . . . . . . .",
+            "3 . . . . . . . .",
+            "2 P . . . . . . .",
+            "1 R . . . K . . .",
+            "  a b c d e f g h",
+        ]
+        .join("\n");
+        assert_eq!(board.to_string(), white_view);
+        assert_eq!(board.to_string_from(PieceColor::White), white_view);
+
+        let black_view = [
+            "1 . . . K . . . R",
+            "2 . . . . . . . P",
+            "3 . . . . . . . .",
+            "4 . . . . . . . .",
+            "5 . . . . . . . .",
+            "6 . . . . . . . .",
+            "7 . . . . . . . .",
+            "8 . . . k . . . .",
+            "  h g f e d c b a",
+        ]
+        .join("\n");
+        assert_eq!(board.to_string_from(PieceColor::Black), black_view);
+    }
+
+    #[test]
+    fn test_to_fen() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 b - - 12 40",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        ] {
+            assert_eq!(Board::from_fen(fen).unwrap().to_fen(), fen);
+        }
+
+        let mut board = Board::starting_position();
+        board.apply_san_line("e4 c5 Nf3").unwrap();
+        assert_eq!(
+            board.to_fen(),
+            "rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2"
+        );
+
+        // No pawn can take on e3, so the square is dropped
+        let board = Board::from_fen("4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1").unwrap();
+        assert_eq!(board.to_fen(), "4k3/8/8/8/4P3/8/8/4K3 b - - 0 1");
+
+        // Nor when the capture would expose the king
+        let board = Board::from_fen("7k/8/8/K2pP2r/8/8/8/8 w - d6 0 1").unwrap();
+        assert_eq!(board.to_fen(), "7k/8/8/K2pP2r/8/8/8/8 w - - 0 1");
+
+        // Malformed en passant squares are errors, not panics
+        for square in ["é", "e", "e33", "i3", "e9"] {
+            let fen = format!("4k3/8/8/8/8/8/8/4K3 w - {} 0 1", square);
+            assert!(Board::from_fen(&fen).is_err());
+        }
+    }
+
+    #[test]
+    fn test_from_fen_whitespace() {
+        // No black pawn can take on e3, so to_fen leaves the en passant square out
+        let fen = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1";
+        for padded in [
+            "  rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1  ",
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR  b   KQkq e3 0 1",
+            "\trnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b\tKQkq e3 0 1\n",
+        ] {
+            assert_eq!(Board::from_fen(padded).unwrap().to_fen(), fen);
+            assert_eq!(Board::from_fen_lenient(padded).unwrap().to_fen(), fen);
+        }
+
+        // Whitespace inside the placement still splits it into too many fields
+        assert!(
+            Board::from_fen("rnbqkbnr/pppppppp/8/8 /4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_from_fen_lenient() {
+        let fen = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3";
+        assert!(Board::from_fen(fen).is_err());
+        let board = Board::from_fen_lenient(fen).unwrap();
+        assert_eq!(board.halfmove_clock(), 0);
+        assert_eq!(board.fullmove_number(), 1);
+        assert_eq!(board.turn(), PieceColor::Black);
+
+        let board = Board::from_fen_lenient(&format!("{} 12", fen)).unwrap();
+        assert_eq!(board.halfmove_clock(), 12);
+        assert_eq!(board.fullmove_number(), 1);
+
+        let board = Board::from_fen_lenient(&format!("{} 3 20", fen)).unwrap();
+        assert_eq!(board.halfmove_clock(), 3);
+        assert_eq!(board.fullmove_number(), 20);
+
+        assert!(
+            Board::from_fen_lenient("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq").is_err()
+        );
+        assert!(Board::from_fen_lenient(&format!("{} x 1", fen)).is_err());
+
+        // Only the lenient parser reads a fullmove number of 0 as 1
+        let board = Board::from_fen_lenient(&format!("{} 0 0", fen)).unwrap();
+        assert_eq!(board.fullmove_number(), 1);
+        assert!(Board::from_fen(&format!("{} 0 0", fen)).is_err());
+        assert!(Board::from_fen_strict(&format!("{} 0 0", fen)).is_err());
+    }
+
+    #[test]
+    fn test_move_clocks() {
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/4P3/R3K3 w - - 5 10").unwrap();
+        board.make_move(Position::new(0, 0), Position::new(0, 5));
+        assert_eq!(board.halfmove_clock(), 6);
+        assert_eq!(board.fullmove_number(), 10);
+
+        board.make_move(Position::new(4, 7), Position::new(3, 7));
+        assert_eq!(board.halfmove_clock(), 7);
+        assert_eq!(board.fullmove_number(), 11);
+
+        board.make_move(Position::new(4, 1), Position::new(4, 3));
+        assert_eq!(board.halfmove_clock(), 0);
+    }
+
+    #[test]
+    fn test_move_clocks_special_moves() {
+        // Castling counts as a quiet move, also when the king takes its own rook's square
+        let mut board = Board::from_fen("r3k3/8/8/8/8/8/8/4K2R w K - 5 10").unwrap();
+        board
+            .make_move_checked(Move::from_uci("e1g1").unwrap())
+            .unwrap();
+        assert_eq!(board.halfmove_clock(), 6);
+        let mut board = Board::new_chess960("4k3/8/8/8/8/8/8/5KR1 w G - 5 10").unwrap();
+        board
+            .make_move_checked(Move::from_uci("f1g1").unwrap())
+            .unwrap();
+        assert_eq!(board.halfmove_clock(), 6);
+
+        // Promotions reset the clock, with or without a capture
+        let mut board = Board::from_fen("1n2k3/P7/8/8/8/8/8/4K3 w - - 5 10").unwrap();
+        board
+            .make_move_checked(Move::from_uci("a7a8q").unwrap())
+            .unwrap();
+        assert_eq!(board.halfmove_clock(), 0);
+        let mut board = Board::from_fen("1n2k3/P7/8/8/8/8/8/4K3 w - - 5 10").unwrap();
+        board
+            .make_move_checked(Move::from_uci("a7b8n").unwrap())
+            .unwrap();
+        assert_eq!(board.halfmove_clock(), 0);
+
+        // The two-step promotion resets it as well
+        let mut board = Board::from_fen("1n2k3/P7/8/8/8/8/8/4K3 w - - 5 10").unwrap();
+        board.make_move(Position::new(0, 6), Position::new(1, 7));
+        board.resolve_promotion(PieceType::Rook).unwrap();
+        assert_eq!(board.halfmove_clock(), 0);
+    }
+
+    #[test]
+    fn test_ply() {
+        let mut board = Board::starting_position();
+        assert_eq!(board.ply(), 0);
+        assert_eq!(fullmove_from_ply(0), 1);
+
+        board.make_uci_move("e2e4").unwrap();
+        assert_eq!(board.ply(), 1);
+        assert_eq!(board.fullmove_number(), 1);
+        assert_eq!(fullmove_from_ply(board.ply()), 1);
+
+        board.make_uci_move("e7e5").unwrap();
+        assert_eq!(board.ply(), 2);
+        assert_eq!(board.fullmove_number(), 2);
+        assert_eq!(fullmove_from_ply(board.ply()), 2);
+
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/4K3 b - - 0 30").unwrap();
+        assert_eq!(board.ply(), 59);
+        assert_eq!(fullmove_from_ply(board.ply()), 30);
+    }
+
+    #[test]
+    fn test_from_epd() {
+        let (board, operations) = Board::from_epd(
+            "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id \"WAC.001\";",
+        )
+        .unwrap();
+        assert_eq!(board.turn(), PieceColor::White);
+        assert_eq!(
+            board[Position::new(6, 2)],
+            Some(Piece {
+                type_: PieceType::Queen,
+                color: PieceColor::White
+            })
+        );
+        assert_eq!(operations.len(), 2);
+        assert_eq!(operations["bm"], "Qg6");
+        assert_eq!(operations["id"], "WAC.001");
+
+        // Quoted operands keep their spacing and may contain semicolons
+        let (_, operations) =
+            Board::from_epd("4k3/8/8/8/8/8/8/4K3 w - - c0 \"a  b; c\";\tid  \"x\"; bm Kd2")
+                .unwrap();
+        assert_eq!(operations.len(), 3);
+        assert_eq!(operations["c0"], "a  b; c");
+        assert_eq!(operations["id"], "x");
+        assert_eq!(operations["bm"], "Kd2");
+
+        // Operations are optional, and the position matches the FEN with default clocks
+        let (board, operations) =
+            Board::from_epd("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -").unwrap();
+        assert!(operations.is_empty());
+        assert_eq!(
+            board.zobrist_hash(),
+            Board::starting_position().zobrist_hash()
+        );
+
+        assert!(Board::from_epd("rnbqkbnr/pppppppp/8/8 w").is_err());
+        assert!(Board::from_epd("8/8/8/8/8/8/8/8 x - - bm e4;").is_err());
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(Board::starting_position().validate().is_ok());
+        assert!(
+            Board::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1")
+                .unwrap()
+                .validate()
+                .is_ok()
+        );
+
+        let invalid_fens = [
+            // Missing black king
+            "8/8/8/8/8/8/8/4K3 w - - 0 1",
+            // Two white kings
+            "4k3/8/8/8/8/8/8/3KK3 w - - 0 1",
+            // Pawn on the first rank
+            "4k3/8/8/8/8/8/8/P3K3 w - - 0 1",
+            // Black is in check but it's white's turn
+            "4k3/8/8/8/8/8/8/4RK2 w - - 0 1",
+            // Kingside castling without a rook on h1
+            "4k3/8/8/8/8/8/8/4K3 w K - 0 1",
+            // Castling with the king off its starting square
+            "4k3/8/8/8/8/8/8/R2K4 w Q - 0 1",
+            // Nobody could have pushed a pawn to make this en passant square
+            "4k3/8/8/8/8/8/8/4K3 w - e6 0 1",
+        ];
+        for fen in invalid_fens {
+            let board = Board::from_fen(fen).unwrap();
+            assert!(
+                matches!(board.validate(), Err(ChessError::InvalidPosition(_))),
+                "{}",
+                fen
+            );
+            assert!(Board::from_fen_strict(fen).is_err());
+        }
+    }
+
+    #[test]
+    fn test_see() {
+        // Pawn takes an undefended knight
+        let board = Board::from_fen("4k3/8/8/3n4/4P3/8/8/4K3 w - - 0 1").unwrap();
+        let capture = Move::new(Position::new(4, 3), Position::new(3, 4));
+        assert_eq!(board.see(capture), 320);
+
+        // Pawn takes a knight defended by a pawn
+        let board = Board::from_fen("4k3/8/2p5/3n4/4P3/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(board.see(capture), 220);
+
+        // Rook takes a pawn defended by a pawn
+        let board = Board::from_fen("4k3/8/2p5/3p4/8/8/8/3RK3 w - - 0 1").unwrap();
+        let capture = Move::new(Position::new(3, 0), Position::new(3, 4));
+        assert_eq!(board.see(capture), -400);
+
+        // Doubled rooks against a single defending rook, the back rook x-rays through
+        let board = Board::from_fen("3rk3/8/8/3p4/8/8/3R4/3RK3 w - - 0 1").unwrap();
+        let capture = Move::new(Position::new(3, 1), Position::new(3, 4));
+        assert_eq!(board.see(capture), 100);
+
+        // The king can't recapture a defended piece
+        let board = Board::from_fen("8/8/8/8/8/3k4/3p4/3RK3 w - - 0 1").unwrap();
+        let capture = Move::new(Position::new(3, 0), Position::new(3, 1));
+        assert_eq!(board.see(capture), 100);
+    }
+
+    #[test]
+    fn test_hanging_pieces() {
+        // The knight is undefended, the bishop is defended by the king against a rook
+        let board = Board::from_fen("r3kr2/8/8/8/N7/8/8/4KB2 w - - 0 1").unwrap();
+        assert_eq!(
+            board.hanging_pieces(PieceColor::White),
+            vec![Position::new(0, 3)]
+        );
+
+        // A defended rook attacked by a pawn still hangs
+        let board = Board::from_fen("4k3/8/8/2p5/1R6/R7/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            board.hanging_pieces(PieceColor::White),
+            vec![Position::new(1, 3)]
+        );
+        assert!(board.hanging_pieces(PieceColor::Black).is_empty());
+
+        // A king attacker only counts against undefended pieces
+        let board = Board::from_fen("8/8/8/8/8/3k4/3p4/3RK3 w - - 0 1").unwrap();
+        assert!(board.hanging_pieces(PieceColor::Black).is_empty());
+        let board = Board::from_fen("8/8/8/8/8/2k5/3R4/7K w - - 0 1").unwrap();
+        assert_eq!(
+            board.hanging_pieces(PieceColor::White),
+            vec![Position::new(3, 1)]
+        );
+        assert!(
+            Board::starting_position()
+                .hanging_pieces(PieceColor::White)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_order_moves() {
+        // Rook and pawn can both take the queen, the knight can take a pawn
+        let board = Board::from_fen("4k3/8/8/3q4/4P3/1p6/8/N2RK3 w - - 0 1").unwrap();
+        let mut moves = board.all_legal_moves();
+        board.order_moves(&mut moves);
+        let queen = Position::new(3, 4);
+        assert_eq!(moves[0], Move::new(Position::new(4, 3), queen));
+        assert_eq!(moves[1], Move::new(Position::new(3, 0), queen));
+        assert_eq!(
+            moves[2],
+            Move::new(Position::new(0, 0), Position::new(1, 2))
+        );
+        assert!(!board.is_move_capture(moves[3]));
+
+        // Promotions come right after captures
+        let board = Board::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let mut moves = board.all_legal_moves();
+        board.order_moves(&mut moves);
+        assert_eq!(
+            moves[0],
+            Move::new_promotion(Position::new(0, 6), Position::new(0, 7), PieceType::Queen)
+        );
+    }
+
+    #[test]
+    fn test_en_passant_horizontal_pin() {
+        // Taking en passant would clear both pawns off the fifth rank and expose the king
+        let board = Board::from_fen("7k/8/8/K2pP2r/8/8/8/8 w - d6 0 1").unwrap();
+        let en_passant = Move::new(Position::new(4, 4), Position::new(3, 5));
+        assert!(!board.move_legal(en_passant));
+        assert!(
+            !board
+                .legal_moves(Position::new(4, 4))
+                .contains(&Position::new(3, 5))
+        );
+        assert!(board.move_legal(Move::new(Position::new(4, 4), Position::new(4, 5))));
+        let mut board_copy = board.clone();
+        assert_eq!(
+            board_copy.make_move(en_passant.from(), en_passant.to()),
+            MoveResult::Illegal
+        );
+
+        // Without the rook the same capture is fine
+        let mut board = Board::from_fen("7k/8/8/K2pP3/8/8/8/8 w - d6 0 1").unwrap();
+        assert_eq!(
+            board.make_move(en_passant.from(), en_passant.to()),
+            MoveResult::Normal
+        );
+        assert!(board[Position::new(3, 4)].is_none());
+        assert!(!board.is_color_in_check(PieceColor::White));
+    }
+
+    #[test]
+    fn test_is_castle() {
+        let board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        assert_eq!(
+            board.is_castle(Move::from_uci("e1g1").unwrap()),
+            Some(CastlingSide::Kingside)
+        );
+        assert_eq!(
+            board.is_castle(Move::from_uci("e1c1").unwrap()),
+            Some(CastlingSide::Queenside)
+        );
+        assert_eq!(board.is_castle(Move::from_uci("e1f1").unwrap()), None);
+        assert_eq!(board.is_castle(Move::from_uci("e1e3").unwrap()), None);
+        // Black can't castle while it's white's turn
+        assert_eq!(board.is_castle(Move::from_uci("e8g8").unwrap()), None);
+
+        let board = Board::new_chess960("6kr/8/8/8/8/8/8/6KR w H - 0 1").unwrap();
+        assert_eq!(
+            board.is_castle(Move::from_uci("g1h1").unwrap()),
+            Some(CastlingSide::Kingside)
+        );
+    }
+
+    #[test]
+    fn test_castling_rights_to_fen() {
+        let fen_rights = |fen: &str| Board::from_fen(fen).unwrap().castling_rights.to_fen();
+        assert_eq!(Board::starting_position().castling_rights.to_fen(), "KQkq");
+        assert_eq!(fen_rights("r3k2r/8/8/8/8/8/8/R3K2R w Kq - 0 1"), "Kq");
+        assert_eq!(fen_rights("r3k2r/8/8/8/8/8/8/R3K2R w qkQK - 0 1"), "KQkq");
+        assert_eq!(fen_rights("r3k2r/8/8/8/8/8/8/R3K2R w - - 0 1"), "-");
+
+        // Chess960 rooks away from the corners keep their file letters
+        let board = Board::new_chess960("1r4kr/8/8/8/8/8/8/1R4KR w HBhb - 0 1").unwrap();
+        assert_eq!(board.castling_rights.to_fen(), "KBkb");
+    }
+
+    #[test]
+    fn test_sliding_attacks() {
+        // Rook on d4, blocked by its own pawn on d6 and an enemy knight on f4
+        let board = Board::from_fen("4k3/8/3P4/8/3R1n2/8/8/4K3 w - - 0 1").unwrap();
+        let mut attacks = board.sliding_attacks(Position::new(3, 3), &Offset::STRAIGHT_DIRECTIONS);
+        attacks.sort_by_key(|pos| (pos.file, pos.rank));
+        let expected = [
+            (0, 3),
+            (1, 3),
+            (2, 3),
+            (3, 0),
+            (3, 1),
+            (3, 2),
+            (3, 4),
+            (4, 3),
+            (5, 3),
+        ];
+        assert_eq!(
+            attacks,
+            expected
+                .iter()
+                .map(|&(file, rank)| Position::new(file, rank))
+                .collect::<Vec<Position>>()
+        );
+
+        // Only the given directions are used
+        let attacks = board.sliding_attacks(Position::new(3, 3), &[Offset::NORTH]);
+        assert_eq!(attacks, vec![Position::new(3, 4)]);
+        // From an empty square the first blocker is always included
+        let attacks = board.sliding_attacks(Position::new(0, 0), &Offset::DIAGONAL_DIRECTIONS);
+        assert_eq!(
+            attacks,
+            vec![
+                Position::new(1, 1),
+                Position::new(2, 2),
+                Position::new(3, 3)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pins() {
+        // Knight on e4 pinned by the rook, bishop on c3 pinned by the queen on a5
+        let board = Board::from_fen("4r1k1/8/8/q7/4N3/2B5/8/4K3 w - - 0 1").unwrap();
+        assert!(board.is_pinned(Position::new(4, 3)));
+        assert_eq!(board.pin_ray(Position::new(4, 3)), Some(Offset::NORTH));
+        assert_eq!(board.pin_ray(Position::new(2, 2)), Some(Offset::NORTH_WEST));
+        assert!(!board.is_pinned(Position::new(4, 0)));
+        assert!(!board.is_pinned(Position::new(4, 7)));
+
+        // A rook doesn't pin along a diagonal, and two pieces in the way means no pin
+        let board = Board::from_fen("6k1/8/8/r7/4N3/2B1N3/8/4K3 w - - 0 1").unwrap();
+        assert!(!board.is_pinned(Position::new(4, 2)));
+        assert!(!board.is_pinned(Position::new(2, 2)));
+        let board = Board::from_fen("4r1k1/8/8/8/4N3/4N3/8/4K3 w - - 0 1").unwrap();
+        assert!(!board.is_pinned(Position::new(4, 2)));
+        assert!(!board.is_pinned(Position::new(4, 3)));
+    }
+
+    #[test]
+    fn test_king_position() {
+        let mut board = Board::starting_position();
+        assert_eq!(
+            board.king_position(PieceColor::White),
+            Some(Position::new(4, 0))
+        );
+        assert_eq!(
+            board.king_position(PieceColor::Black),
+            Some(Position::new(4, 7))
+        );
+
+        board.make_uci_move("e2e4").unwrap();
+        board.make_uci_move("e7e5").unwrap();
+        board.make_uci_move("e1e2").unwrap();
+        assert_eq!(
+            board.king_position(PieceColor::White),
+            Some(Position::new(4, 1))
+        );
+
+        // Castling moves the king too, also when it lands on its own rook's square in Chess960
+        let mut board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        board.make_uci_move("e1g1").unwrap();
+        assert_eq!(
+            board.king_position(PieceColor::White),
+            Some(Position::new(6, 0))
+        );
+        let mut board = Board::new_chess960("6kr/8/8/8/8/8/8/6KR w H - 0 1").unwrap();
+        board.make_uci_move("g1h1").unwrap();
+        assert_eq!(
+            board.king_position(PieceColor::White),
+            Some(Position::new(6, 0))
+        );
+
+        assert_eq!(Board::empty().king_position(PieceColor::White), None);
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/8 w - - 0 1").unwrap();
+        assert_eq!(board.king_position(PieceColor::White), None);
+
+        // With two white kings the first one found is used, also after one of them moves
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/K3K3 w - - 0 1").unwrap();
+        board
+            .make_move_checked(Move::from_uci("e1e2").unwrap())
+            .unwrap();
+        assert_eq!(
+            board.king_position(PieceColor::White),
+            Some(Position::new(0, 0))
+        );
+        assert_eq!(
+            board.flipped().king_position(PieceColor::White),
+            Some(Position::new(4, 0))
+        );
+    }
+
+    #[test]
+    fn test_checkers() {
+        let board = Board::starting_position();
+        assert!(board.checkers(PieceColor::White).is_empty());
+
+        // Rook on e8 and knight on d3 both check the white king
+        let board = Board::from_fen("k3r3/8/8/8/8/3n4/8/4K3 w - - 0 1").unwrap();
+        let mut checkers = board.checkers(PieceColor::White);
+        checkers.sort_by_key(|pos| pos.rank);
+        assert_eq!(checkers, vec![Position::new(3, 2), Position::new(4, 7)]);
+        assert!(board.checkers(PieceColor::Black).is_empty());
+
+        // Blocked rooks don't give check
+        let board = Board::from_fen("kn2R3/8/8/8/8/8/8/4K3 b - - 0 1").unwrap();
+        assert!(board.checkers(PieceColor::Black).is_empty());
+        // Works for the side not to move too
+        let board = Board::from_fen("k6R/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(board.checkers(PieceColor::Black), vec![Position::new(7, 7)]);
+    }
+
+    #[test]
+    fn test_forced_king_only() {
+        assert!(!Board::starting_position().forced_king_only());
+
+        // Double check from the rook and the bishop, the knight can't help
+        let board = Board::from_fen("4k3/8/8/1B6/8/8/3n4/4R1K1 b - - 0 1").unwrap();
+        assert!(board.is_double_check());
+        assert!(board.forced_king_only());
+
+        // A single check that can be blocked
+        let board = Board::from_fen("4k3/8/8/8/8/8/3n4/4R1K1 b - - 0 1").unwrap();
+        assert!(!board.forced_king_only());
+
+        // No legal moves at all
+        let board = Board::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1").unwrap();
+        assert!(!board.forced_king_only());
+    }
+
+    #[test]
+    fn test_checkmate() {
+        // Black king on g8, white king on g6, white rook on a8
+        let board = Board::from_fen("R5k1/8/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert!(board.is_checkmate());
+    }
 
     #[test]
     fn test_stalemate() {
@@ -968,6 +4150,517 @@ mod tests {
         assert!(board.is_stalemate());
     }
 
+    #[test]
+    fn test_status() {
+        let board = Board::starting_position();
+        assert_eq!(board.status(), GameStatus::Ongoing);
+        assert!(!board.is_game_over());
+
+        let board = Board::from_fen("R5k1/8/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(board.status(), GameStatus::Checkmate);
+        assert!(board.is_game_over());
+
+        let board = Board::from_fen("1k6/1P6/1K6/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(board.status(), GameStatus::Stalemate);
+        assert!(board.is_game_over());
+
+        // King and bishop against king can't mate
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/2B1K3 w - - 0 1").unwrap();
+        assert_eq!(board.status(), GameStatus::InsufficientMaterial);
+        assert!(board.is_game_over());
+
+        // A single pawn is enough to play on
+        let board = Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+        assert_eq!(board.status(), GameStatus::Ongoing);
+    }
+
+    #[test]
+    fn test_insufficient_material_bishops() {
+        // (FEN, drawn), c1 and f8 are dark squares while f1 and c8 are light
+        let cases = [
+            ("4k3/8/8/8/8/8/8/2B1K3 w - - 0 1", true),
+            ("4k3/8/8/8/8/8/8/4KB2 w - - 0 1", true),
+            // One bishop each, on the same or opposite colors
+            ("2b1k3/8/8/8/8/8/8/4KB2 w - - 0 1", true),
+            ("4kb2/8/8/8/8/8/8/2B1K3 w - - 0 1", true),
+            ("4kb2/8/8/8/8/8/8/4KB2 w - - 0 1", false),
+            ("2b1k3/8/8/8/8/8/8/2B1K3 w - - 0 1", false),
+            // Two bishops for one side
+            ("4k3/8/8/8/8/8/8/B1B1K3 w - - 0 1", true),
+            ("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1", false),
+            ("2b1kb2/8/8/8/8/8/8/4K3 b - - 0 1", false),
+            ("4kb1b/8/8/8/8/8/8/4K3 b - - 0 1", true),
+            // Knights mixed in
+            ("4k3/8/8/8/8/8/8/4KN2 w - - 0 1", true),
+            ("4kb2/8/8/8/8/8/8/4KN2 w - - 0 1", false),
+            ("4k3/8/8/8/8/8/8/3NKN2 w - - 0 1", false),
+        ];
+        for (fen, drawn) in cases {
+            let board = Board::from_fen(fen).unwrap();
+            assert_eq!(board.is_insufficient_material(), drawn, "{}", fen);
+        }
+    }
+
+    #[test]
+    fn test_insufficient_material_after_capture() {
+        // Taking the last rook leaves king and knight against king
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/3r4/4K1N1 w - - 0 1").unwrap();
+        assert_eq!(board.status(), GameStatus::Ongoing);
+        board
+            .make_move_checked(Move::from_uci("e1d2").unwrap())
+            .unwrap();
+        assert_eq!(board.status(), GameStatus::InsufficientMaterial);
+        assert_eq!(board.draw_reason(), Some(DrawReason::InsufficientMaterial));
+
+        // With two knights left mate can still happen, so the game goes on
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/3r4/4KNN1 w - - 0 1").unwrap();
+        board
+            .make_move_checked(Move::from_uci("e1d2").unwrap())
+            .unwrap();
+        assert!(!board.is_insufficient_material());
+        assert_eq!(board.status(), GameStatus::Ongoing);
+        assert_eq!(board.draw_reason(), None);
+    }
+
+    #[test]
+    fn test_draw_reason() {
+        let mut board = Board::starting_position();
+        assert_eq!(board.draw_reason(), None);
+
+        // Shuffling the knights back and forth repeats the starting position
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+        for uci in shuffle.iter().chain(shuffle.iter()) {
+            assert_eq!(board.draw_reason(), None);
+            board.make_uci_move(uci).unwrap();
+        }
+        assert_eq!(board.repetition_count(), 3);
+        assert_eq!(board.draw_reason(), Some(DrawReason::ThreefoldRepetition));
+        for uci in shuffle.iter().chain(shuffle.iter()) {
+            board.make_uci_move(uci).unwrap();
+        }
+        assert_eq!(board.repetition_count(), 5);
+        assert_eq!(board.draw_reason(), Some(DrawReason::FivefoldRepetition));
+
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/4P3/R3K3 w - - 99 80").unwrap();
+        assert_eq!(board.draw_reason(), None);
+        board.make_move(Position::new(0, 0), Position::new(0, 5));
+        assert_eq!(board.draw_reason(), Some(DrawReason::FiftyMove));
+
+        let board = Board::from_fen("4k3/8/8/8/8/8/4P3/R3K3 w - - 150 80").unwrap();
+        assert_eq!(board.draw_reason(), Some(DrawReason::SeventyFiveMove));
+
+        // Checkmate on the fiftieth move still wins
+        let board = Board::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 100 80").unwrap();
+        assert_eq!(board.draw_reason(), None);
+
+        let board = Board::from_fen("k7/1R6/1K6/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(board.draw_reason(), Some(DrawReason::Stalemate));
+        let board = Board::from_fen("k7/8/1K6/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(board.draw_reason(), Some(DrawReason::InsufficientMaterial));
+    }
+
+    #[test]
+    fn test_claimable_and_automatic_draws() {
+        let mut board = Board::starting_position();
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+        for uci in shuffle.iter().chain(shuffle.iter()) {
+            assert!(!board.can_claim_draw());
+            board.make_uci_move(uci).unwrap();
+        }
+        // Threefold can be claimed but the game goes on until fivefold
+        assert!(board.can_claim_draw());
+        assert_eq!(board.status(), GameStatus::Ongoing);
+        for uci in shuffle.iter().chain(shuffle.iter()) {
+            board.make_uci_move(uci).unwrap();
+        }
+        assert_eq!(board.status(), GameStatus::FivefoldRepetition);
+        assert!(board.is_game_over());
+
+        let board = Board::from_fen("4k3/8/8/8/8/8/4P3/R3K3 w - - 99 80").unwrap();
+        assert!(!board.can_claim_draw());
+        let board = Board::from_fen("4k3/8/8/8/8/8/4P3/R3K3 w - - 100 80").unwrap();
+        assert!(board.can_claim_draw());
+        assert_eq!(board.status(), GameStatus::Ongoing);
+        let board = Board::from_fen("4k3/8/8/8/8/8/4P3/R3K3 w - - 149 80").unwrap();
+        assert_eq!(board.status(), GameStatus::Ongoing);
+        let board = Board::from_fen("4k3/8/8/8/8/8/4P3/R3K3 w - - 150 80").unwrap();
+        assert_eq!(board.status(), GameStatus::SeventyFiveMove);
+
+        // Mate on the seventy-fifth move still counts
+        let board = Board::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 150 80").unwrap();
+        assert_eq!(board.status(), GameStatus::Checkmate);
+    }
+
+    #[test]
+    fn test_repetition_in_history() {
+        let mut board = Board::starting_position();
+        assert!(!board.is_repetition_in_history(100));
+        for uci in ["g1f3", "g8f6", "f3g1"] {
+            board.make_uci_move(uci).unwrap();
+            assert!(!board.is_repetition_in_history(100));
+        }
+        board.make_uci_move("f6g8").unwrap();
+        assert!(board.is_repetition_in_history(100));
+        assert!(board.is_repetition_in_history(4));
+        assert!(!board.is_repetition_in_history(3));
+
+        // A pawn move makes the earlier positions unreachable
+        let mut board = Board::starting_position();
+        for uci in [
+            "g1f3", "g8f6", "f3g1", "f6g8", "e2e4", "e7e5", "g1f3", "g8f6",
+        ] {
+            board.make_uci_move(uci).unwrap();
+        }
+        assert!(!board.is_repetition_in_history(100));
+        board.make_uci_move("f3g1").unwrap();
+        board.make_uci_move("f6g8").unwrap();
+        assert!(board.is_repetition_in_history(100));
+    }
+
+    #[test]
+    fn test_terminal_state() {
+        assert_eq!(Board::starting_position().terminal_state(), None);
+        let board = Board::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1").unwrap();
+        assert_eq!(board.terminal_state(), Some(GameStatus::Checkmate));
+        let board = Board::from_fen("k7/1R6/1K6/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(board.terminal_state(), Some(GameStatus::Stalemate));
+        let board = Board::from_fen("k7/8/1K6/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(
+            board.terminal_state(),
+            Some(GameStatus::InsufficientMaterial)
+        );
+    }
+
+    #[test]
+    fn test_winner() {
+        let board = Board::from_fen("R5k1/8/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(board.winner(), Some(PieceColor::White));
+
+        let board = Board::from_fen("r5K1/8/6k1/8/8/8/8/8 w - - 0 1").unwrap();
+        assert_eq!(board.winner(), Some(PieceColor::Black));
+
+        // Draws and ongoing games have no winner
+        let board = Board::from_fen("1k6/1P6/1K6/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(board.winner(), None);
+        assert_eq!(Board::starting_position().winner(), None);
+    }
+
+    #[test]
+    fn test_set_turn() {
+        let mut board = Board::from_fen("r3k3/8/8/8/8/8/8/4K3 w q - 0 1").unwrap();
+        board.set_turn(PieceColor::Black);
+        assert_eq!(board.turn(), PieceColor::Black);
+        assert!(board.castling_rights.can_castle(PieceColor::Black, false));
+
+        let result = board.make_move(Position::new(4, 7), Position::new(2, 7));
+        assert_eq!(result, MoveResult::Normal);
+        assert_eq!(board.turn(), PieceColor::White);
+
+        board.set_turn(PieceColor::White);
+        assert_eq!(board.turn(), PieceColor::White);
+
+        // The rest of the state is left alone
+        let mut board = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+        board.set_turn(PieceColor::White);
+        assert_eq!(board.en_passant_target, Some(Position::new(3, 5)));
+        assert_eq!(board.with_mover(PieceColor::Black).en_passant_target, None);
+    }
+
+    #[test]
+    fn test_set_castling_rights() {
+        let mut board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w - - 0 1").unwrap();
+        let castle_kingside = Move::new(Position::new(4, 0), Position::new(6, 0));
+        assert!(!board.move_legal(castle_kingside));
+
+        board.set_castling_rights(CastlingRights::from_flags(true, false, false, true));
+        assert_eq!(board.castling_rights().to_fen(), "Kq");
+        assert!(board.move_legal(castle_kingside));
+        assert!(!board.move_legal(Move::new(Position::new(4, 0), Position::new(2, 0))));
+
+        board.set_castling_rights(CastlingRights::from_flags(false, false, false, false));
+        assert_eq!(board.castling_rights().to_fen(), "-");
+        assert!(!board.move_legal(castle_kingside));
+    }
+
+    #[test]
+    fn test_array_conversion() {
+        let mut pieces = [[None; 8]; 8];
+        pieces[0][4] = Some(Piece {
+            type_: PieceType::King,
+            color: PieceColor::White,
+        });
+        pieces[7][4] = Some(Piece {
+            type_: PieceType::King,
+            color: PieceColor::Black,
+        });
+        pieces[1][0] = Some(Piece {
+            type_: PieceType::Pawn,
+            color: PieceColor::White,
+        });
+
+        let board = Board::from_array(pieces);
+        let pawn = board.piece_at_pos(Position::new(0, 1)).unwrap();
+        assert_eq!(
+            (pawn.type_, pawn.color),
+            (PieceType::Pawn, PieceColor::White)
+        );
+        assert_eq!(board.turn(), PieceColor::White);
+        assert_eq!(board.all_legal_moves().len(), 7);
+
+        // Converting back gives the same layout
+        let starting = Board::starting_position();
+        let array = starting.to_array();
+        let queen = array[7][3].unwrap();
+        assert_eq!(
+            (queen.type_, queen.color),
+            (PieceType::Queen, PieceColor::Black)
+        );
+        assert!(array[3][3].is_none());
+        assert_eq!(Board::from_array(array).iter_pieces().count(), 32);
+
+        // Kings on their home squares get no castling rights from the round trip
+        let kings_only = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let board = Board::from_array(kings_only.to_array());
+        assert!(board.validate().is_ok());
+        assert_eq!(board.to_fen(), kings_only.to_fen());
+    }
+
+    #[test]
+    fn test_null_move() {
+        let mut board = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+        let hash = board.zobrist_hash();
+
+        board.make_null_move();
+        assert_eq!(board.turn(), PieceColor::Black);
+        assert_eq!(board.en_passant_target, None);
+
+        board.unmake_null_move();
+        assert_eq!(board.turn(), PieceColor::White);
+        assert_eq!(board.en_passant_target, Some(Position::new(3, 5)));
+        assert_eq!(board.zobrist_hash(), hash);
+
+        // Unmaking without a null move does nothing
+        board.unmake_null_move();
+        assert_eq!(board.turn(), PieceColor::White);
+    }
+
+    #[test]
+    fn test_make_move_checked() {
+        let mut board = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+
+        let illegal = Move::new(Position::new(4, 0), Position::new(4, 2));
+        assert_eq!(
+            board.make_move_checked(illegal),
+            Err(ChessError::IllegalMove(illegal))
+        );
+
+        // Nothing on e2, and the turn doesn't pass
+        let empty_source = Move::new(Position::new(4, 1), Position::new(4, 3));
+        assert_eq!(
+            board.make_move_checked(empty_source),
+            Err(ChessError::NoPieceAtSource(Position::new(4, 1)))
+        );
+        assert_eq!(
+            board.make_uci_move("a2a3"),
+            Err(ChessError::NoPieceAtSource(Position::new(0, 1)))
+        );
+        assert_eq!(board.turn(), PieceColor::White);
+
+        // En passant reports the pawn taken from d5
+        let en_passant = Move::new(Position::new(4, 4), Position::new(3, 5));
+        let info = board.make_move_checked(en_passant).unwrap();
+        assert!(info.is_en_passant);
+        assert!(!info.is_castling);
+        assert_eq!(info.piece.type_, PieceType::Pawn);
+        assert_eq!(info.captured.unwrap().color, PieceColor::Black);
+        assert!(board.piece_at_pos(Position::new(3, 4)).is_none());
+
+        let mut board = Board::from_fen("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let no_piece = Move::new(Position::new(0, 6), Position::new(1, 7));
+        assert_eq!(
+            board.make_move_checked(no_piece),
+            Err(ChessError::PromotionRequired(no_piece))
+        );
+        let promotion =
+            Move::new_promotion(Position::new(0, 6), Position::new(1, 7), PieceType::Queen);
+        let info = board.make_move_checked(promotion).unwrap();
+        assert_eq!(info.captured.unwrap().type_, PieceType::Rook);
+        assert_eq!(
+            board.piece_at_pos(Position::new(1, 7)).unwrap().type_,
+            PieceType::Queen
+        );
+    }
+
+    #[test]
+    fn test_make_move_with_capture() {
+        let mut board = Board::from_fen("n3k3/8/8/3pP3/8/8/8/R3K3 w - d6 0 1").unwrap();
+        let en_passant = Move::new(Position::new(4, 4), Position::new(3, 5));
+        assert_eq!(
+            board.make_move_with_capture(en_passant),
+            Ok(Some(Piece {
+                type_: PieceType::Pawn,
+                color: PieceColor::Black
+            }))
+        );
+
+        let quiet_move = Move::new(Position::new(4, 7), Position::new(3, 7));
+        assert_eq!(board.make_move_with_capture(quiet_move), Ok(None));
+
+        let capture = Move::new(Position::new(0, 0), Position::new(0, 7));
+        assert_eq!(
+            board.make_move_with_capture(capture),
+            Ok(Some(Piece {
+                type_: PieceType::Knight,
+                color: PieceColor::Black
+            }))
+        );
+
+        let illegal_move = Move::new(Position::new(3, 7), Position::new(3, 0));
+        assert_eq!(
+            board.make_move_with_capture(illegal_move),
+            Err(ChessError::IllegalMove(illegal_move))
+        );
+    }
+
+    #[test]
+    fn test_make_move_with_status() {
+        let mut board = Board::starting_position();
+        for uci in ["f2f3", "e7e5", "g2g4"] {
+            assert_eq!(
+                board.make_move_with_status(Move::from_uci(uci).unwrap()),
+                Ok(GameStatus::Ongoing)
+            );
+        }
+        assert_eq!(
+            board.make_move_with_status(Move::from_uci("d8h4").unwrap()),
+            Ok(GameStatus::Checkmate)
+        );
+
+        // Taking the last pawn leaves bare kings
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/3p4/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            board.make_move_with_status(Move::from_uci("e1d2").unwrap()),
+            Ok(GameStatus::InsufficientMaterial)
+        );
+
+        let illegal_move = Move::from_uci("e2e4").unwrap();
+        assert_eq!(
+            board.make_move_with_status(illegal_move),
+            Err(ChessError::NoPieceAtSource(illegal_move.from()))
+        );
+    }
+
+    #[test]
+    fn test_make_uci_move() {
+        let mut board = Board::starting_position();
+        for uci in ["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "g8f6", "e1g1"] {
+            board.make_uci_move(uci).unwrap();
+        }
+        let king = board.piece_at_pos(Position::new(6, 0)).unwrap();
+        assert_eq!(king.type_, PieceType::King);
+        let rook = board.piece_at_pos(Position::new(5, 0)).unwrap();
+        assert_eq!(rook.type_, PieceType::Rook);
+
+        assert!(matches!(
+            board.make_uci_move("e5e4"),
+            Err(ChessError::IllegalMove(_))
+        ));
+        assert!(matches!(
+            board.make_uci_move("e9e4"),
+            Err(ChessError::InvalidUci(_))
+        ));
+
+        let mut board = Board::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        board.make_uci_move("a7a8n").unwrap();
+        let knight = board.piece_at_pos(Position::new(0, 7)).unwrap();
+        assert_eq!(knight.type_, PieceType::Knight);
+    }
+
+    #[test]
+    fn test_make_moves() {
+        let uci = |moves: &[&str]| -> Vec<Move> {
+            moves
+                .iter()
+                .map(|uci| Move::from_uci(uci).unwrap())
+                .collect()
+        };
+
+        let mut board = Board::starting_position();
+        board.make_moves(&uci(&["e2e4", "e7e5", "g1f3"])).unwrap();
+        assert_eq!(
+            board.to_fen(),
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2"
+        );
+
+        // The third move is illegal, so the first two are taken back
+        let fen = board.to_fen();
+        assert_eq!(
+            board.make_moves(&uci(&["b8c6", "f1c4", "c6c4"])),
+            Err(ChessError::IllegalMoveInLine(
+                2,
+                Move::from_uci("c6c4").unwrap()
+            ))
+        );
+        assert_eq!(board.to_fen(), fen);
+        assert_eq!(board.last_move(), Some(Move::from_uci("g1f3").unwrap()));
+
+        // Promotions need their piece
+        let mut board = Board::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            board.make_moves(&uci(&["a7a8"])),
+            Err(ChessError::IllegalMoveInLine(
+                0,
+                Move::from_uci("a7a8").unwrap()
+            ))
+        );
+        board.make_moves(&uci(&["a7a8q", "e8d7"])).unwrap();
+        assert!(board.make_moves(&[]).is_ok());
+    }
+
+    #[test]
+    fn test_from_uci_position() {
+        let board = Board::from_uci_position("startpos").unwrap();
+        assert_eq!(board.to_fen(), Board::starting_position().to_fen());
+
+        let board = Board::from_uci_position("startpos moves e2e4 e7e5 g1f3").unwrap();
+        assert_eq!(
+            board.to_fen(),
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2"
+        );
+        assert_eq!(board.last_move(), Some(Move::from_uci("g1f3").unwrap()));
+
+        let board = Board::from_uci_position("fen 4k3/P7/8/8/8/8/8/4K3 w - - 0 1 moves a7a8q e8d7")
+            .unwrap();
+        assert_eq!(board.to_fen(), "Q7/3k4/8/8/8/8/8/4K3 w - - 1 2");
+        let board = Board::from_uci_position("fen 4k3/8/8/8/8/8/8/4K3 b - - 0 1").unwrap();
+        assert_eq!(board.turn(), PieceColor::Black);
+
+        assert!(matches!(
+            Board::from_uci_position("startpos moves e2e4 e2e4"),
+            Err(ChessError::NoPieceAtSource(_))
+        ));
+        assert!(matches!(
+            Board::from_uci_position("startpos moves e2e5"),
+            Err(ChessError::IllegalMove(_))
+        ));
+        assert!(matches!(
+            Board::from_uci_position("startpos moves e2"),
+            Err(ChessError::InvalidUci(_))
+        ));
+        assert!(matches!(
+            Board::from_uci_position("fen 4k3/8/8 w - - 0 1 moves e1e2"),
+            Err(ChessError::InvalidPosition(_))
+        ));
+        assert!(matches!(
+            Board::from_uci_position("startpos e2e4"),
+            Err(ChessError::InvalidPosition(_))
+        ));
+        assert!(matches!(
+            Board::from_uci_position(""),
+            Err(ChessError::InvalidPosition(_))
+        ));
+    }
+
     #[test]
     fn test_promotion() {
         let mut board = Board::from_fen("8/P7/8/8/8/8/8/8 w - - 0 1").unwrap();
@@ -987,5 +4680,159 @@ mod tests {
         let mut board = Board::from_fen("8/8/P7/8/8/8/8/8 w - - 0 1").unwrap();
         let result = board.make_move(Position::new(0, 5), Position::new(0, 7));
         assert_eq!(result, MoveResult::Illegal);
+
+        // Moves carrying a promotion piece must be promotions to a valid piece
+        let board = Board::from_fen("8/P7/8/8/8/8/4P3/8 w - - 0 1").unwrap();
+        let promotion =
+            Move::new_promotion(Position::new(0, 6), Position::new(0, 7), PieceType::Knight);
+        assert!(board.move_legal(promotion));
+        let to_king =
+            Move::new_promotion(Position::new(0, 6), Position::new(0, 7), PieceType::King);
+        assert!(!board.move_legal(to_king));
+        let not_promotion =
+            Move::new_promotion(Position::new(4, 1), Position::new(4, 2), PieceType::Queen);
+        assert!(!board.move_legal(not_promotion));
+    }
+
+    #[test]
+    fn test_castling_side_targets() {
+        let side = CastlingSide::Kingside;
+        assert_eq!(side.king_target(PieceColor::White), Position::new(6, 0));
+        assert_eq!(side.rook_target(PieceColor::White), Position::new(5, 0));
+
+        let side = CastlingSide::Queenside;
+        assert_eq!(side.king_target(PieceColor::Black), Position::new(2, 7));
+        assert_eq!(side.rook_target(PieceColor::Black), Position::new(3, 7));
+
+        // The pieces land on the same squares in Chess960
+        let mut board =
+            Board::new_chess960("bqnbrkrn/pppppppp/8/8/8/8/PPPPPPPP/BQNBRKRN w KQkq - 0 1")
+                .unwrap();
+        board.make_move(Position::new(5, 0), Position::new(6, 0));
+        let side = CastlingSide::Kingside;
+        assert_eq!(
+            board.piece_at_pos(side.king_target(PieceColor::White)),
+            Some(Piece {
+                type_: PieceType::King,
+                color: PieceColor::White
+            })
+        );
+        assert_eq!(
+            board.piece_at_pos(side.rook_target(PieceColor::White)),
+            Some(Piece {
+                type_: PieceType::Rook,
+                color: PieceColor::White
+            })
+        );
+    }
+
+    #[test]
+    fn test_is_in_check_at() {
+        // The rook on e8 checks the king on e1 but not a king on d1
+        let board = Board::from_fen("4r1k1/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert!(board.is_in_check_at(PieceColor::White, Position::new(4, 0)));
+        assert!(!board.is_in_check_at(PieceColor::White, Position::new(3, 0)));
+        assert!(!board.is_in_check_at(PieceColor::Black, Position::new(6, 7)));
+        assert_eq!(
+            board.is_in_check(),
+            board.is_in_check_at(
+                PieceColor::White,
+                board.king_position(PieceColor::White).unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn test_is_legal() {
+        let board = Board::starting_position();
+        assert!(board.is_legal(Position::new(4, 1), Position::new(4, 3), None));
+        assert!(!board.is_legal(Position::new(4, 1), Position::new(4, 4), None));
+        assert!(!board.is_legal(Position::new(4, 6), Position::new(4, 4), None));
+
+        // Castling and en passant are recognised from the squares alone
+        let board = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K2R w K d6 0 1").unwrap();
+        assert!(board.is_legal(Position::new(4, 0), Position::new(6, 0), None));
+        assert!(board.is_legal(Position::new(4, 4), Position::new(3, 5), None));
+
+        // Promotions may leave out the piece, but can't promote to a king
+        let board = Board::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let (from, to) = (Position::new(0, 6), Position::new(0, 7));
+        assert!(board.is_legal(from, to, None));
+        assert!(board.is_legal(from, to, Some(PieceType::Queen)));
+        assert!(!board.is_legal(from, to, Some(PieceType::King)));
+        assert!(!board.is_legal(
+            Position::new(4, 0),
+            Position::new(4, 1),
+            Some(PieceType::Queen)
+        ));
+    }
+
+    #[test]
+    fn test_en_passant_victim() {
+        assert_eq!(Board::starting_position().en_passant_victim(), None);
+
+        let mut board = Board::starting_position();
+        board.make_move(Position::new(4, 1), Position::new(4, 3));
+        assert_eq!(board.en_passant_victim(), Some(Position::new(4, 3)));
+
+        let board = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+        assert_eq!(board.en_passant_victim(), Some(Position::new(3, 4)));
+    }
+
+    #[test]
+    fn test_can_castle_now() {
+        let board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        assert!(board.can_castle_now(CastlingSide::Kingside));
+        assert!(board.can_castle_now(CastlingSide::Queenside));
+
+        // The rights are still there, but b1 is blocked or f1 is attacked
+        let board = Board::from_fen("r3k2r/8/8/8/8/8/8/RN2K2R w KQkq - 0 1").unwrap();
+        assert!(!board.can_castle_now(CastlingSide::Queenside));
+        let board = Board::from_fen("r3kr2/8/8/8/8/8/8/R3K2R w KQq - 0 1").unwrap();
+        assert!(board.castling_rights().can_castle(PieceColor::White, true));
+        assert!(!board.can_castle_now(CastlingSide::Kingside));
+        assert!(!Board::starting_position().can_castle_now(CastlingSide::Kingside));
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let board = Board::starting_position();
+        let bytes = board.to_bytes();
+        assert_eq!(bytes.len(), BOARD_BYTES);
+        // a1 is a white rook and b1 a white knight, the a-file black pawn sits in the low bits
+        assert_eq!(bytes[0], 0x24);
+        assert_eq!(bytes[24], 0x99);
+        assert_eq!(Board::from_bytes(&bytes).unwrap().to_fen(), board.to_fen());
+
+        // The en passant square is kept even when no pawn can take, unlike in FEN
+        let mut board = Board::starting_position();
+        board.make_move(Position::new(4, 1), Position::new(4, 3));
+        let decoded = Board::from_bytes(&board.to_bytes()).unwrap();
+        assert_eq!(decoded.to_bytes(), board.to_bytes());
+        assert_eq!(decoded.en_passant_victim(), Some(Position::new(4, 3)));
+
+        let board = Board::from_fen("r3k2r/8/8/3pP3/8/8/8/R3K2R w Kq d6 17 42").unwrap();
+        let decoded = Board::from_bytes(&board.to_bytes()).unwrap();
+        assert_eq!(decoded.to_fen(), board.to_fen());
+        assert_eq!(decoded.halfmove_clock(), 17);
+
+        let board = Board::new_chess960("bqnbrkrn/pppppppp/8/8/8/8/PPPPPPPP/BQNBRKRN w GEge - 0 1")
+            .unwrap();
+        let decoded = Board::from_bytes(&board.to_bytes()).unwrap();
+        assert!(decoded.is_chess960());
+        assert_eq!(decoded.to_fen(), board.to_fen());
+        assert!(decoded.move_legal(Move::from_uci("f1g1").unwrap()));
+
+        assert!(Board::from_bytes(&[]).is_err());
+        let mut bytes = Board::starting_position().to_bytes();
+        bytes[0] = 0x07;
+        assert!(Board::from_bytes(&bytes).is_err());
+        let mut bytes = Board::starting_position().to_bytes();
+        bytes[35] = 64;
+        assert!(Board::from_bytes(&bytes).is_err());
+        // to_bytes never writes a fullmove number of 0
+        let mut bytes = Board::starting_position().to_bytes();
+        bytes[40] = 0;
+        assert!(Board::from_bytes(&bytes).is_err());
     }
 }
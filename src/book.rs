@@ -0,0 +1,123 @@
+use crate::board::Board;
+use crate::error::ChessError;
+use crate::piece::Move;
+use std::collections::HashMap;
+
+// Lines played from the starting position for HashMapBook::standard
+const STANDARD_LINES: [&str; 6] = [
+    "e4 e5 Nf3 Nc6 Bb5 a6",
+    "e4 c5 Nf3 d6 d4 cxd4",
+    "e4 e6 d4 d5",
+    "d4 d5 c4 e6 Nc3 Nf6",
+    "d4 Nf6 c4 e6 Nf3 b6",
+    "c4 e5 Nc3 Nf6",
+];
+
+// Something an engine can ask for a move before it starts searching
+pub trait OpeningBook {
+    fn best(&self, board: &Board) -> Option<Move>;
+}
+
+// Book moves keyed by the Zobrist hash of the position they are played from
+#[derive(Clone, Debug, Default)]
+pub struct HashMapBook {
+    moves: HashMap<u64, Vec<Move>>,
+}
+
+impl HashMapBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // A small book with a few common openings
+    pub fn standard() -> Self {
+        let mut book = Self::new();
+        for line in STANDARD_LINES {
+            book.add_line(line).expect("Standard book lines are legal");
+        }
+        book
+    }
+
+    // Moves added earlier for the same position are preferred
+    pub fn add(&mut self, board: &Board, move_: Move) {
+        let moves = self.moves.entry(board.zobrist_hash()).or_default();
+        if !moves.contains(&move_) {
+            moves.push(move_);
+        }
+    }
+
+    // Adds every move of a SAN line played from the starting position
+    pub fn add_line(&mut self, line: &str) -> Result<(), ChessError> {
+        Board::starting_position().play_san_line(line, |board, move_| self.add(board, move_))
+    }
+
+    pub fn moves(&self, board: &Board) -> &[Move] {
+        self.moves
+            .get(&board.zobrist_hash())
+            .map_or(&[], Vec::as_slice)
+    }
+}
+
+impl OpeningBook for HashMapBook {
+    // The first stored move that is legal, in case of a hash collision
+    fn best(&self, board: &Board) -> Option<Move> {
+        self.moves(board)
+            .iter()
+            .copied()
+            .find(|&move_| board.move_legal(move_))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::Board;
+    use crate::book::{HashMapBook, OpeningBook};
+    use crate::error::ChessError;
+    use crate::piece::Move;
+
+    #[test]
+    fn test_hash_map_book() {
+        let book = HashMapBook::standard();
+        let mut board = Board::starting_position();
+        assert_eq!(book.best(&board), Some(Move::from_uci("e2e4").unwrap()));
+        assert_eq!(book.moves(&board).len(), 3);
+
+        // 1. e4 e5 2. Nf3 is followed by Nc6 in the book
+        board.apply_san_line("e4 e5 Nf3").unwrap();
+        assert_eq!(book.best(&board), Some(Move::from_uci("b8c6").unwrap()));
+
+        // Reached by a different move order, the position is still found
+        let mut board = Board::starting_position();
+        board.apply_san_line("d4 e6 c4 d5").unwrap();
+        assert_eq!(book.best(&board), Some(Move::from_uci("b1c3").unwrap()));
+
+        // Out of book
+        let mut board = Board::starting_position();
+        board.apply_san_line("h4 h5").unwrap();
+        assert_eq!(book.best(&board), None);
+        assert!(book.moves(&board).is_empty());
+    }
+
+    #[test]
+    fn test_hash_map_book_add() {
+        let mut book = HashMapBook::new();
+        let board = Board::starting_position();
+        assert_eq!(book.best(&board), None);
+
+        book.add(&board, Move::from_uci("g1f3").unwrap());
+        book.add(&board, Move::from_uci("g1f3").unwrap());
+        book.add_line("d4 d5").unwrap();
+        assert_eq!(
+            book.moves(&board),
+            &[
+                Move::from_uci("g1f3").unwrap(),
+                Move::from_uci("d2d4").unwrap()
+            ]
+        );
+
+        assert_eq!(
+            book.add_line("e4 e4"),
+            Err(ChessError::InvalidSanInLine(1, "e4".to_string()))
+        );
+    }
+}
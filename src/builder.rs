@@ -0,0 +1,169 @@
+use crate::board::{Board, CastlingRights, Position};
+use crate::error::ChessError;
+use crate::piece::{Piece, PieceColor};
+
+// Sets up a position piece by piece, checking that it's a valid position on build
+#[derive(Clone, Debug)]
+pub struct BoardBuilder {
+    pieces: Vec<(Position, Piece)>,
+    turn: PieceColor,
+    castling: CastlingRights,
+    en_passant: Option<Position>,
+}
+
+impl BoardBuilder {
+    // An empty board with white to move and no castling rights
+    pub fn new() -> Self {
+        Self {
+            pieces: Vec::new(),
+            turn: PieceColor::White,
+            castling: CastlingRights::from_flags(false, false, false, false),
+            en_passant: None,
+        }
+    }
+
+    // A later piece on the same square replaces the earlier one
+    pub fn piece(mut self, pos: Position, piece: Piece) -> Self {
+        self.pieces.push((pos, piece));
+        self
+    }
+
+    pub fn turn(mut self, color: PieceColor) -> Self {
+        self.turn = color;
+        self
+    }
+
+    pub fn castling(mut self, rights: CastlingRights) -> Self {
+        self.castling = rights;
+        self
+    }
+
+    pub fn en_passant(mut self, pos: Position) -> Self {
+        self.en_passant = Some(pos);
+        self
+    }
+
+    pub fn build(self) -> Result<Board, ChessError> {
+        let mut pieces = [[None; 8]; 8];
+        for (pos, piece) in self.pieces {
+            if !pos.is_on_board() {
+                return Err(ChessError::InvalidPosition(format!(
+                    "Piece placed off the board at {:?}",
+                    pos
+                )));
+            }
+            pieces[pos.rank as usize][pos.file as usize] = Some(piece);
+        }
+
+        let mut board = Board::from_array(pieces);
+        board.set_turn(self.turn);
+        board.set_castling_rights(self.castling);
+        board.set_en_passant_target(self.en_passant);
+        board.validate()?;
+        Ok(board)
+    }
+}
+
+impl Default for BoardBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::{CastlingRights, Position};
+    use crate::builder::BoardBuilder;
+    use crate::error::ChessError;
+    use crate::piece::{Piece, PieceColor, PieceType};
+
+    fn piece(type_: PieceType, color: PieceColor) -> Piece {
+        Piece { type_, color }
+    }
+
+    #[test]
+    fn test_board_builder() {
+        let board = BoardBuilder::new()
+            .piece(
+                Position::new(4, 0),
+                piece(PieceType::King, PieceColor::White),
+            )
+            .piece(
+                Position::new(7, 0),
+                piece(PieceType::Rook, PieceColor::White),
+            )
+            .piece(
+                Position::new(4, 7),
+                piece(PieceType::King, PieceColor::Black),
+            )
+            .piece(
+                Position::new(3, 4),
+                piece(PieceType::Pawn, PieceColor::Black),
+            )
+            .piece(
+                Position::new(4, 4),
+                piece(PieceType::Pawn, PieceColor::White),
+            )
+            .castling(CastlingRights::from_flags(true, false, false, false))
+            .en_passant(Position::new(3, 5))
+            .build()
+            .unwrap();
+        assert_eq!(board.to_fen(), "4k3/8/8/3pP3/8/8/8/4K2R w K d6 0 1");
+
+        let board = BoardBuilder::new()
+            .piece(
+                Position::new(4, 0),
+                piece(PieceType::King, PieceColor::White),
+            )
+            .piece(
+                Position::new(4, 7),
+                piece(PieceType::King, PieceColor::Black),
+            )
+            .turn(PieceColor::Black)
+            .build()
+            .unwrap();
+        assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/8/4K3 b - - 0 1");
+    }
+
+    #[test]
+    fn test_board_builder_validation() {
+        // Two white kings
+        let result = BoardBuilder::new()
+            .piece(
+                Position::new(4, 0),
+                piece(PieceType::King, PieceColor::White),
+            )
+            .piece(
+                Position::new(0, 0),
+                piece(PieceType::King, PieceColor::White),
+            )
+            .piece(
+                Position::new(4, 7),
+                piece(PieceType::King, PieceColor::Black),
+            )
+            .build();
+        assert!(matches!(result, Err(ChessError::InvalidPosition(_))));
+
+        // Castling rights without a rook
+        let result = BoardBuilder::new()
+            .piece(
+                Position::new(4, 0),
+                piece(PieceType::King, PieceColor::White),
+            )
+            .piece(
+                Position::new(4, 7),
+                piece(PieceType::King, PieceColor::Black),
+            )
+            .castling(CastlingRights::from_flags(true, false, false, false))
+            .build();
+        assert!(matches!(result, Err(ChessError::InvalidPosition(_))));
+
+        let result = BoardBuilder::new()
+            .piece(
+                Position::new(8, 0),
+                piece(PieceType::King, PieceColor::White),
+            )
+            .build();
+        assert!(matches!(result, Err(ChessError::InvalidPosition(_))));
+    }
+}
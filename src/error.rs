@@ -0,0 +1,71 @@
+use crate::board::Position;
+use crate::piece::Move;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChessError {
+    IllegalMove(Move),
+    NoPieceAtSource(Position),
+    PromotionRequired(Move),
+    PromotionPending,
+    InvalidUci(String),
+    InvalidSan(String),
+    // Index of the failing move in a line of moves, and the move itself
+    InvalidSanInLine(usize, String),
+    // Index of the failing move in a list of moves, and the move itself
+    IllegalMoveInLine(usize, Move),
+    InvalidPosition(String),
+}
+
+impl fmt::Display for ChessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChessError::IllegalMove(move_) => write!(f, "Illegal move: {}", move_),
+            ChessError::NoPieceAtSource(pos) => {
+                write!(f, "No piece to move on {}", pos.to_algebraic())
+            }
+            ChessError::PromotionRequired(move_) => {
+                write!(f, "Move needs a promotion piece: {}", move_)
+            }
+            ChessError::PromotionPending => write!(f, "A promotion is waiting to be resolved"),
+            ChessError::InvalidUci(uci) => write!(f, "Invalid UCI move: {}", uci),
+            ChessError::InvalidSan(san) => write!(f, "Invalid or illegal SAN move: {}", san),
+            ChessError::InvalidSanInLine(index, san) => {
+                write!(f, "Invalid or illegal SAN move {} at index {}", san, index)
+            }
+            ChessError::IllegalMoveInLine(index, move_) => {
+                write!(f, "Illegal move {} at index {}", move_, index)
+            }
+            ChessError::InvalidPosition(reason) => write!(f, "Invalid position: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for ChessError {}
+
+// The reason a castling move isn't allowed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CastleError {
+    NoRights,
+    RookMissing,
+    PathBlocked,
+    InCheck,
+    ThroughCheck,
+    IntoCheck,
+}
+
+impl fmt::Display for CastleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            CastleError::NoRights => "Castling rights on this side are gone",
+            CastleError::RookMissing => "The castling rook is not on its square",
+            CastleError::PathBlocked => "Pieces stand between the king and the rook",
+            CastleError::InCheck => "You can't castle out of check",
+            CastleError::ThroughCheck => "The king can't pass through an attacked square",
+            CastleError::IntoCheck => "The king can't castle into check",
+        };
+        write!(f, "{}", reason)
+    }
+}
+
+impl std::error::Error for CastleError {}
@@ -0,0 +1,280 @@
+use crate::board::{BOARD_HEIGHT, BOARD_WIDTH, Board, Position};
+use crate::piece::{PieceColor, PieceType};
+
+pub const MATE_SCORE: i32 = 100_000;
+
+const MOBILITY_WEIGHT: i32 = 2;
+
+// Piece-square tables from white's point of view, a1 is the first entry
+#[rustfmt::skip]
+const PAWN_TABLE: [i32; 64] = [
+     0,  0,   0,   0,   0,   0,  0,  0,
+     5, 10,  10, -20, -20,  10, 10,  5,
+     5, -5, -10,   0,   0, -10, -5,  5,
+     0,  0,   0,  20,  20,   0,  0,  0,
+     5,  5,  10,  25,  25,  10,  5,  5,
+    10, 10,  20,  30,  30,  20, 10, 10,
+    50, 50,  50,  50,  50,  50, 50, 50,
+     0,  0,   0,   0,   0,   0,  0,  0,
+];
+
+#[rustfmt::skip]
+const KNIGHT_TABLE: [i32; 64] = [
+    -50, -40, -30, -30, -30, -30, -40, -50,
+    -40, -20,   0,   5,   5,   0, -20, -40,
+    -30,   5,  10,  15,  15,  10,   5, -30,
+    -30,   0,  15,  20,  20,  15,   0, -30,
+    -30,   5,  15,  20,  20,  15,   5, -30,
+    -30,   0,  10,  15,  15,  10,   0, -30,
+    -40, -20,   0,   0,   0,   0, -20, -40,
+    -50, -40, -30, -30, -30, -30, -40, -50,
+];
+
+#[rustfmt::skip]
+const BISHOP_TABLE: [i32; 64] = [
+    -20, -10, -10, -10, -10, -10, -10, -20,
+    -10,   5,   0,   0,   0,   0,   5, -10,
+    -10,  10,  10,  10,  10,  10,  10, -10,
+    -10,   0,  10,  10,  10,  10,   0, -10,
+    -10,   5,   5,  10,  10,   5,   5, -10,
+    -10,   0,   5,  10,  10,   5,   0, -10,
+    -10,   0,   0,   0,   0,   0,   0, -10,
+    -20, -10, -10, -10, -10, -10, -10, -20,
+];
+
+#[rustfmt::skip]
+const ROOK_TABLE: [i32; 64] = [
+     0,  0,  0,  5,  5,  0,  0,  0,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+     5, 10, 10, 10, 10, 10, 10,  5,
+     0,  0,  0,  0,  0,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+const QUEEN_TABLE: [i32; 64] = [
+    -20, -10, -10, -5, -5, -10, -10, -20,
+    -10,   0,   5,  0,  0,   0,   0, -10,
+    -10,   5,   5,  5,  5,   5,   0, -10,
+      0,   0,   5,  5,  5,   5,   0,  -5,
+     -5,   0,   5,  5,  5,   5,   0,  -5,
+    -10,   0,   5,  5,  5,   5,   0, -10,
+    -10,   0,   0,  0,  0,   0,   0, -10,
+    -20, -10, -10, -5, -5, -10, -10, -20,
+];
+
+#[rustfmt::skip]
+const KING_TABLE: [i32; 64] = [
+     20,  30,  10,   0,   0,  10,  30,  20,
+     20,  20,   0,   0,   0,   0,  20,  20,
+    -10, -20, -20, -20, -20, -20, -20, -10,
+    -20, -30, -30, -40, -40, -30, -30, -20,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+];
+
+fn square_bonus(piece_type: PieceType, color: PieceColor, pos: Position) -> i32 {
+    // Black reads the tables upside down
+    let rank = match color {
+        PieceColor::White => pos.rank,
+        PieceColor::Black => BOARD_HEIGHT - 1 - pos.rank,
+    };
+    let index = (rank * BOARD_WIDTH + pos.file) as usize;
+    let table = match piece_type {
+        PieceType::Pawn => &PAWN_TABLE,
+        PieceType::Knight => &KNIGHT_TABLE,
+        PieceType::Bishop => &BISHOP_TABLE,
+        PieceType::Rook => &ROOK_TABLE,
+        PieceType::Queen => &QUEEN_TABLE,
+        PieceType::King => &KING_TABLE,
+    };
+    table[index]
+}
+
+impl Board {
+    // Centipawn score from the side to move's point of view
+    pub fn evaluate(&self) -> i32 {
+        let legal_move_count = self.count_legal_moves() as i32;
+        if legal_move_count == 0 {
+            return if self.is_in_check() { -MATE_SCORE } else { 0 };
+        }
+
+        let current_color = self.turn();
+        let mut score = 0;
+        for (pos, piece) in self.iter_pieces() {
+            let piece_score = piece.type_.value() + square_bonus(piece.type_, piece.color, pos);
+            if piece.color == current_color {
+                score += piece_score;
+            } else {
+                score -= piece_score;
+            }
+        }
+
+        score + MOBILITY_WEIGHT * legal_move_count
+    }
+
+    // Pawns with no enemy pawns in front of them on their own or adjacent files
+    pub fn passed_pawns(&self, color: PieceColor) -> Vec<Position> {
+        let enemy_color = match color {
+            PieceColor::White => PieceColor::Black,
+            PieceColor::Black => PieceColor::White,
+        };
+        let enemy_pawns = self.find_pieces(PieceType::Pawn, enemy_color);
+        self.find_pieces(PieceType::Pawn, color)
+            .into_iter()
+            .filter(|pawn| {
+                !enemy_pawns.iter().any(|enemy| {
+                    let ahead = match color {
+                        PieceColor::White => enemy.rank > pawn.rank,
+                        PieceColor::Black => enemy.rank < pawn.rank,
+                    };
+                    ahead && (enemy.file - pawn.file).abs() <= 1
+                })
+            })
+            .collect()
+    }
+
+    // Pawns sharing their file with another friendly pawn
+    pub fn doubled_pawns(&self, color: PieceColor) -> Vec<Position> {
+        let pawns = self.find_pieces(PieceType::Pawn, color);
+        pawns
+            .iter()
+            .copied()
+            .filter(|pawn| pawns.iter().filter(|other| other.file == pawn.file).count() > 1)
+            .collect()
+    }
+
+    // Pawns with no friendly pawns on the adjacent files
+    pub fn isolated_pawns(&self, color: PieceColor) -> Vec<Position> {
+        let pawns = self.find_pieces(PieceType::Pawn, color);
+        pawns
+            .iter()
+            .copied()
+            .filter(|pawn| {
+                !pawns
+                    .iter()
+                    .any(|other| (other.file - pawn.file).abs() == 1)
+            })
+            .collect()
+    }
+
+    // Files without pawns of either color
+    pub fn open_files(&self) -> Vec<i8> {
+        let pawns: Vec<Position> = self
+            .find_pieces(PieceType::Pawn, PieceColor::White)
+            .into_iter()
+            .chain(self.find_pieces(PieceType::Pawn, PieceColor::Black))
+            .collect();
+        (0..BOARD_WIDTH)
+            .filter(|&file| !pawns.iter().any(|pawn| pawn.file == file))
+            .collect()
+    }
+
+    // Files without friendly pawns but with at least one enemy pawn
+    pub fn half_open_files(&self, color: PieceColor) -> Vec<i8> {
+        let enemy_color = match color {
+            PieceColor::White => PieceColor::Black,
+            PieceColor::Black => PieceColor::White,
+        };
+        let own_pawns = self.find_pieces(PieceType::Pawn, color);
+        let enemy_pawns = self.find_pieces(PieceType::Pawn, enemy_color);
+        (0..BOARD_WIDTH)
+            .filter(|&file| {
+                !own_pawns.iter().any(|pawn| pawn.file == file)
+                    && enemy_pawns.iter().any(|pawn| pawn.file == file)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::{Board, Position};
+    use crate::eval::MATE_SCORE;
+    use crate::piece::PieceColor;
+
+    #[test]
+    fn test_passed_pawns() {
+        assert!(
+            Board::starting_position()
+                .passed_pawns(PieceColor::White)
+                .is_empty()
+        );
+
+        // The a-pawn is free, the d-pawn is stopped by the pawn on e6
+        let board = Board::from_fen("4k3/8/4p3/8/P2P4/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            board.passed_pawns(PieceColor::White),
+            vec![Position::new(0, 3)]
+        );
+        // The d-pawn also blocks the e-pawn from black's side
+        assert!(board.passed_pawns(PieceColor::Black).is_empty());
+
+        let board = Board::from_fen("4k3/8/8/8/8/p7/7P/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            board.passed_pawns(PieceColor::Black),
+            vec![Position::new(0, 2)]
+        );
+    }
+
+    #[test]
+    fn test_pawn_structure() {
+        let board = Board::starting_position();
+        assert!(board.doubled_pawns(PieceColor::White).is_empty());
+        assert!(board.isolated_pawns(PieceColor::Black).is_empty());
+
+        // Doubled c-pawns next to an isolated a-pawn, and a lone h-pawn for black
+        let board = Board::from_fen("4k3/7p/8/8/2P5/2P5/P2P4/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            board.doubled_pawns(PieceColor::White),
+            vec![Position::new(2, 2), Position::new(2, 3)]
+        );
+        assert_eq!(
+            board.isolated_pawns(PieceColor::White),
+            vec![Position::new(0, 1)]
+        );
+        assert!(board.doubled_pawns(PieceColor::Black).is_empty());
+        assert_eq!(
+            board.isolated_pawns(PieceColor::Black),
+            vec![Position::new(7, 6)]
+        );
+    }
+
+    #[test]
+    fn test_open_files() {
+        let board = Board::starting_position();
+        assert!(board.open_files().is_empty());
+        assert!(board.half_open_files(PieceColor::White).is_empty());
+
+        // The c-file is open, and white has no pawns left on the d- and e-files
+        let board = Board::from_fen("4k3/pp2pppp/8/3p4/8/8/PP3PPP/4K3 w - - 0 1").unwrap();
+        assert_eq!(board.open_files(), vec![2]);
+        assert_eq!(board.half_open_files(PieceColor::White), vec![3, 4]);
+        assert!(board.half_open_files(PieceColor::Black).is_empty());
+    }
+
+    #[test]
+    fn test_evaluate() {
+        // Symmetric position, only the mobility term remains
+        let board = Board::starting_position();
+        assert_eq!(board.evaluate(), 40);
+        assert_eq!(board.evaluate(), board.flipped().evaluate());
+
+        // White is a queen up, and it's bad for black to move
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/3QK3 w - - 0 1").unwrap();
+        assert!(board.evaluate() > 800);
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/3QK3 b - - 0 1").unwrap();
+        assert!(board.evaluate() < -800);
+
+        // Checkmate and stalemate
+        let board = Board::from_fen("R5k1/8/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(board.evaluate(), -MATE_SCORE);
+        let board = Board::from_fen("1k6/1P6/1K6/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(board.evaluate(), 0);
+    }
+}
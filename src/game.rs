@@ -0,0 +1,109 @@
+use crate::board::{Board, GameStatus, MoveInfo};
+use crate::error::ChessError;
+use crate::piece::Move;
+
+// A board together with the moves that led to it
+#[derive(Clone)]
+pub struct Game {
+    board: Board,
+    history: Vec<MoveInfo>,
+    status: GameStatus,
+}
+
+impl Game {
+    pub fn new() -> Self {
+        Self::from_board(Board::starting_position())
+    }
+
+    pub fn from_board(board: Board) -> Self {
+        let status = board.status();
+        Self {
+            board,
+            history: Vec::new(),
+            status,
+        }
+    }
+
+    pub fn push(&mut self, move_: Move) -> Result<MoveInfo, ChessError> {
+        let info = self.board.make_move_checked(move_)?;
+        self.history.push(info);
+        self.status = self.board.status();
+        Ok(info)
+    }
+
+    // Takes back the last move, returning it. Moves played on the board before it was
+    // handed to from_board are kept.
+    pub fn undo(&mut self) -> Option<MoveInfo> {
+        let info = self.history.pop()?;
+        self.board.unmake_move();
+        self.status = self.board.status();
+        Some(info)
+    }
+
+    pub fn current_board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn moves(&self) -> &[MoveInfo] {
+        &self.history
+    }
+
+    pub fn result(&self) -> GameStatus {
+        self.status
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::{Board, GameStatus, Position};
+    use crate::game::Game;
+    use crate::piece::Move;
+
+    #[test]
+    fn test_game() {
+        let mut game = Game::new();
+        assert_eq!(game.result(), GameStatus::Ongoing);
+        assert!(game.moves().is_empty());
+        assert!(game.undo().is_none());
+
+        // Fool's mate
+        for uci in ["f2f3", "e7e5", "g2g4", "d8h4"] {
+            game.push(Move::from_uci(uci).unwrap()).unwrap();
+        }
+        assert_eq!(game.result(), GameStatus::Checkmate);
+        assert_eq!(game.moves().len(), 4);
+        assert_eq!(game.moves()[3].move_.to(), Position::new(7, 3));
+        assert!(game.push(Move::from_uci("e1f2").unwrap()).is_err());
+        assert_eq!(game.moves().len(), 4);
+
+        let undone = game.undo().unwrap();
+        assert_eq!(undone.move_, Move::from_uci("d8h4").unwrap());
+        assert_eq!(game.result(), GameStatus::Ongoing);
+        assert_eq!(game.moves().len(), 3);
+        for _ in 0..3 {
+            game.undo().unwrap();
+        }
+        assert_eq!(
+            game.current_board().to_fen(),
+            Board::starting_position().to_fen()
+        );
+
+        let game = Game::from_board(Board::from_fen("k7/8/1K6/8/8/8/8/8 b - - 0 1").unwrap());
+        assert_eq!(game.result(), GameStatus::InsufficientMaterial);
+
+        // Undo stops at the board the game started from
+        let mut board = Board::starting_position();
+        board.make_uci_move("e2e4").unwrap();
+        let mut game = Game::from_board(board.clone());
+        game.push(Move::from_uci("e7e5").unwrap()).unwrap();
+        assert!(game.undo().is_some());
+        assert!(game.undo().is_none());
+        assert_eq!(game.current_board().to_fen(), board.to_fen());
+    }
+}
@@ -0,0 +1,105 @@
+use crate::board::Board;
+use crate::error::ChessError;
+use serde::{Deserialize, Serialize};
+
+// JSON form of a board, the fields follow the FEN fields
+#[derive(Serialize, Deserialize)]
+pub(crate) struct BoardJson {
+    placement: String,
+    turn: String,
+    castling: String,
+    en_passant: Option<String>,
+    halfmove_clock: u32,
+    fullmove_number: u32,
+    #[serde(default)]
+    chess960: bool,
+}
+
+impl From<Board> for BoardJson {
+    fn from(board: Board) -> Self {
+        let fen = board.to_fen();
+        let fields: Vec<&str> = fen.split(' ').collect();
+        Self {
+            placement: fields[0].to_string(),
+            turn: fields[1].to_string(),
+            castling: fields[2].to_string(),
+            en_passant: (fields[3] != "-").then(|| fields[3].to_string()),
+            halfmove_clock: board.halfmove_clock(),
+            fullmove_number: board.fullmove_number(),
+            chess960: board.is_chess960(),
+        }
+    }
+}
+
+impl TryFrom<BoardJson> for Board {
+    type Error = ChessError;
+
+    fn try_from(json: BoardJson) -> Result<Self, Self::Error> {
+        let fen = format!(
+            "{} {} {} {} {} {}",
+            json.placement,
+            json.turn,
+            json.castling,
+            json.en_passant.as_deref().unwrap_or("-"),
+            json.halfmove_clock,
+            json.fullmove_number
+        );
+        let board = if json.chess960 {
+            Board::new_chess960(&fen)
+        } else {
+            Board::from_fen(&fen)
+        };
+        board.map_err(ChessError::InvalidPosition)
+    }
+}
+
+impl Board {
+    pub fn from_json(json: &str) -> Result<Self, ChessError> {
+        serde_json::from_str(json).map_err(|error| ChessError::InvalidPosition(error.to_string()))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Board always serializes")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::Board;
+    use crate::error::ChessError;
+
+    #[test]
+    fn test_json_round_trip() {
+        let board =
+            Board::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3")
+                .unwrap();
+        let json = board.to_json();
+        assert_eq!(
+            json,
+            "{\"placement\":\"rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR\",\"turn\":\"w\",\
+             \"castling\":\"KQkq\",\"en_passant\":\"f6\",\"halfmove_clock\":0,\
+             \"fullmove_number\":3,\"chess960\":false}"
+        );
+        assert_eq!(Board::from_json(&json).unwrap().to_fen(), board.to_fen());
+
+        // chess960 can be left out, the castling rights are kept but written as KQkq without it
+        let board = Board::from_json(
+            r#"{"placement":"6kr/8/8/8/8/8/8/6KR","turn":"b","castling":"Hh",
+                "en_passant":null,"halfmove_clock":4,"fullmove_number":20}"#,
+        )
+        .unwrap();
+        assert_eq!(board.to_fen(), "6kr/8/8/8/8/8/8/6KR b Kk - 4 20");
+
+        assert!(matches!(
+            Board::from_json("{\"placement\":\"8/8\"}"),
+            Err(ChessError::InvalidPosition(_))
+        ));
+        assert!(matches!(
+            Board::from_json(
+                r#"{"placement":"8/8/8/8/8/8/8/8","turn":"x","castling":"-",
+                    "en_passant":null,"halfmove_clock":0,"fullmove_number":1}"#
+            ),
+            Err(ChessError::InvalidPosition(_))
+        ));
+    }
+}
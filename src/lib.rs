@@ -1,8 +1,33 @@
 mod board;
+mod book;
+mod builder;
+mod error;
+mod eval;
+mod game;
+#[cfg(feature = "serde")]
+mod json;
+mod mover;
+mod perft;
 mod piece;
+mod san;
+mod search;
+mod zobrist;
 
-pub use board::{Board, MoveResult, Position};
-pub use piece::PieceType;
+pub use board::{
+    BOARD_BYTES, BOARD_HEIGHT, BOARD_WIDTH, Board, CastlingRights, CastlingSide, DrawReason,
+    GameStatus, MoveInfo, MoveResult, MoveStats, Position, fullmove_from_ply,
+};
+pub use book::{HashMapBook, OpeningBook};
+pub use builder::BoardBuilder;
+pub use error::{CastleError, ChessError};
+pub use eval::MATE_SCORE;
+pub use game::Game;
+#[cfg(feature = "rand")]
+pub use mover::RandomMover;
+pub use mover::{GreedyMover, MoveSource, SearchMover};
+pub use perft::run_perft_suite;
+pub use piece::{Move, Offset, Piece, PieceColor, PieceType};
+pub use search::TranspositionTable;
 
 #[cfg(test)]
 mod tests {
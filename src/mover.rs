@@ -0,0 +1,172 @@
+use crate::board::Board;
+use crate::piece::{Move, PieceColor};
+use crate::search::TranspositionTable;
+
+// Something that picks the move to play for a side, returning None when there is no legal move.
+// The moves it returns are full moves that Board::make_move_checked accepts.
+pub trait MoveSource {
+    fn choose(&mut self, board: &Board) -> Option<Move>;
+}
+
+// Material of color minus the material of the other side
+fn material_balance(board: &Board, color: PieceColor) -> i32 {
+    board
+        .iter_pieces()
+        .map(|(_, piece)| {
+            if piece.color == color {
+                piece.type_.value()
+            } else {
+                -piece.type_.value()
+            }
+        })
+        .sum()
+}
+
+// Picks uniformly among the legal moves
+#[cfg(feature = "rand")]
+pub struct RandomMover<R: rand::Rng> {
+    rng: R,
+}
+
+#[cfg(feature = "rand")]
+impl<R: rand::Rng> RandomMover<R> {
+    pub fn new(rng: R) -> Self {
+        Self { rng }
+    }
+}
+
+#[cfg(feature = "rand")]
+impl<R: rand::Rng> MoveSource for RandomMover<R> {
+    fn choose(&mut self, board: &Board) -> Option<Move> {
+        use rand::seq::IndexedRandom;
+
+        board.all_legal_moves().choose(&mut self.rng).copied()
+    }
+}
+
+// Plays the move that leaves it with the most material, the first such move on ties
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GreedyMover;
+
+impl MoveSource for GreedyMover {
+    fn choose(&mut self, board: &Board) -> Option<Move> {
+        let color = board.turn();
+        let mut best: Option<(Move, i32)> = None;
+        for move_ in board.all_legal_moves() {
+            let mut child = board.position_copy();
+            if child.make_move_checked(move_).is_err() {
+                continue;
+            }
+            let balance = material_balance(&child, color);
+            if best.is_none_or(|(_, best_balance)| balance > best_balance) {
+                best = Some((move_, balance));
+            }
+        }
+        best.map(|(move_, _)| move_)
+    }
+}
+
+// Negamax search with a transposition table kept between moves
+pub struct SearchMover {
+    max_depth: u8,
+    max_nodes: u64,
+    table: TranspositionTable,
+}
+
+impl SearchMover {
+    pub fn new(max_depth: u8, max_nodes: u64) -> Self {
+        Self {
+            max_depth,
+            max_nodes,
+            table: TranspositionTable::default(),
+        }
+    }
+}
+
+impl MoveSource for SearchMover {
+    fn choose(&mut self, board: &Board) -> Option<Move> {
+        let (best_move, _) =
+            board.search_with_table(self.max_depth, self.max_nodes, &mut self.table);
+        best_move
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::{Board, GameStatus};
+    use crate::game::Game;
+    use crate::mover::{GreedyMover, MoveSource, SearchMover};
+    use crate::piece::{Move, PieceType};
+
+    #[test]
+    fn test_greedy_mover() {
+        // The rook can take a pawn or the queen
+        let board = Board::from_fen("4k3/8/8/3q4/8/8/3p4/3RK3 w - - 0 1").unwrap();
+        assert_eq!(
+            GreedyMover.choose(&board),
+            Some(Move::from_uci("d1d2").unwrap())
+        );
+        let board = Board::from_fen("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1").unwrap();
+        assert_eq!(
+            GreedyMover.choose(&board),
+            Some(Move::from_uci("d1d5").unwrap())
+        );
+
+        // Promotions come with a piece
+        let board = Board::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let move_ = GreedyMover.choose(&board).unwrap();
+        assert_eq!(move_.promotion(), Some(PieceType::Queen));
+
+        let board = Board::from_fen("R5k1/8/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(GreedyMover.choose(&board), None);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_random_mover() {
+        use crate::mover::RandomMover;
+        use rand::SeedableRng;
+
+        let mut mover = RandomMover::new(rand::rngs::StdRng::seed_from_u64(0));
+        let board = Board::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        for _ in 0..20 {
+            let mut child = board.clone();
+            assert!(
+                child
+                    .make_move_checked(mover.choose(&board).unwrap())
+                    .is_ok()
+            );
+        }
+
+        let board = Board::from_fen("R5k1/8/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(mover.choose(&board), None);
+    }
+
+    #[test]
+    fn test_search_mover() {
+        let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let mut mover = SearchMover::new(3, u64::MAX);
+        assert_eq!(mover.choose(&board), Some(Move::from_uci("a1a8").unwrap()));
+
+        let board = Board::from_fen("7k/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(mover.choose(&board), Some(Move::from_uci("a7a8q").unwrap()));
+    }
+
+    #[test]
+    fn test_move_source_game_loop() {
+        let mut sources: [Box<dyn MoveSource>; 2] =
+            [Box::new(SearchMover::new(1, 1000)), Box::new(GreedyMover)];
+        let mut game = Game::new();
+        for ply in 0..20 {
+            let Some(move_) = sources[ply % 2].choose(game.current_board()) else {
+                break;
+            };
+            game.push(move_).unwrap();
+        }
+        // None only comes back once the game is over, never from a spent node budget
+        assert!(game.moves().len() == 20 || game.result() != GameStatus::Ongoing);
+
+        let mut mover = SearchMover::new(4, 0);
+        assert!(mover.choose(&Board::starting_position()).is_some());
+    }
+}
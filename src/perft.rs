@@ -0,0 +1,171 @@
+use crate::board::Board;
+use crate::piece::Move;
+
+impl Board {
+    // Counts leaf nodes of the legal move tree, each promotion piece is its own move
+    pub fn perft(&self, depth: u8) -> u64 {
+        let mut board = self.position_copy();
+        board.perft_in_place(depth, &mut None)
+    }
+
+    // Like perft, but calls f with the board at every leaf. Slower, since the last ply is
+    // played out instead of only counted.
+    pub fn perft_with<F: FnMut(&Board)>(&self, depth: u8, f: &mut F) -> u64 {
+        let mut board = self.position_copy();
+        board.perft_in_place(depth, &mut Some(f))
+    }
+
+    // Plays and unmakes the moves on one board instead of copying it for every child
+    fn perft_in_place(&mut self, depth: u8, on_leaf: &mut Option<&mut dyn FnMut(&Board)>) -> u64 {
+        if depth == 0 {
+            if let Some(f) = on_leaf {
+                f(self);
+            }
+            return 1;
+        }
+
+        let legal_moves = self.legal_moves_in_place();
+
+        if depth == 1 && on_leaf.is_none() {
+            return legal_moves.len() as u64;
+        }
+
+        let mut nodes = 0;
+        for move_ in legal_moves {
+            nodes += self.perft_child(move_, depth - 1, on_leaf);
+        }
+        nodes
+    }
+
+    fn perft_child(
+        &mut self,
+        move_: Move,
+        depth: u8,
+        on_leaf: &mut Option<&mut dyn FnMut(&Board)>,
+    ) -> u64 {
+        self.play_move(move_).expect("Move was legal");
+        let nodes = self.perft_in_place(depth, on_leaf);
+        self.unmake_move();
+        nodes
+    }
+}
+
+// Runs lines like `FEN ;D1 20 ;D2 400`, reporting whether every depth matched
+pub fn run_perft_suite(suite: &str) -> Vec<(String, bool)> {
+    suite
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let mut parts = line.split(';');
+            let fen = parts.next().unwrap_or_default().trim().to_string();
+            (fen.clone(), perft_line_passes(&fen, parts))
+        })
+        .collect()
+}
+
+fn perft_line_passes<'a>(fen: &str, depth_entries: impl Iterator<Item = &'a str>) -> bool {
+    let Ok(board) = Board::from_fen(fen) else {
+        return false;
+    };
+
+    let mut checked_any = false;
+    for entry in depth_entries {
+        let entry = entry.trim();
+        if entry.is_empty() {
+            continue;
+        }
+        let Some((depth, expected)) = entry.split_once(char::is_whitespace) else {
+            return false;
+        };
+        let (Some(Ok(depth)), Ok(expected)) = (
+            depth.strip_prefix('D').map(str::parse::<u8>),
+            expected.trim().parse::<u64>(),
+        ) else {
+            return false;
+        };
+        if board.perft(depth) != expected {
+            return false;
+        }
+        checked_any = true;
+    }
+    checked_any
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::Board;
+    use crate::perft::run_perft_suite;
+
+    #[test]
+    fn test_perft_special_moves() {
+        // Castling, en passant and promotions, including captures that promote
+        let board =
+            Board::from_fen("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1")
+                .unwrap();
+        assert_eq!(board.perft(1), 6);
+        assert_eq!(board.perft(2), 264);
+        assert_eq!(board.perft(3), 9467);
+        assert_eq!(board.perft(4), 422333);
+
+        // Kiwipete
+        let board =
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        assert_eq!(board.perft(1), 48);
+        assert_eq!(board.perft(2), 2039);
+        assert_eq!(board.perft(3), 97862);
+
+        // Position 5
+        let board =
+            Board::from_fen("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8").unwrap();
+        assert_eq!(board.perft(1), 44);
+        assert_eq!(board.perft(2), 1486);
+        assert_eq!(board.perft(3), 62379);
+    }
+
+    #[test]
+    fn test_run_perft_suite() {
+        let suite = "
+            rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 ;D1 20 ;D2 400
+            n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1 ;D1 24 ;D2 496 ;D3 9483
+            r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1 ;D1 48 ;D2 2039
+            4k3/8/8/8/8/8/8/4K3 w - - 0 1 ;D1 6
+            4k3/8/8/8/8/8/8/4K3 w - - 0 1 ;D1 five
+            not a fen ;D1 20
+        ";
+        let results = run_perft_suite(suite);
+        assert_eq!(results.len(), 6);
+        assert_eq!(
+            results[0].0,
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+        );
+        let passed: Vec<bool> = results.iter().map(|(_, passed)| *passed).collect();
+        assert_eq!(passed, vec![true, true, true, false, false, false]);
+    }
+
+    #[test]
+    fn test_perft_with() {
+        let board = Board::starting_position();
+        let mut leaves = 0;
+        assert_eq!(board.perft_with(2, &mut |_| leaves += 1), 400);
+        assert_eq!(leaves, 400);
+
+        // Fool's mate is the only mate after two moves each
+        let mut mates = 0;
+        let nodes = board.perft_with(4, &mut |leaf| {
+            if leaf.is_checkmate() {
+                mates += 1;
+            }
+        });
+        assert_eq!(nodes, 197281);
+        assert_eq!(mates, 8);
+
+        // Each promotion piece is its own leaf
+        let board = Board::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let mut fens = Vec::new();
+        board.perft_with(1, &mut |leaf| fens.push(leaf.to_fen()));
+        assert_eq!(fens.len(), board.perft(1) as usize);
+        assert!(fens.contains(&"N3k3/8/8/8/8/8/8/4K3 b - - 0 1".to_string()));
+    }
+}
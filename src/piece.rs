@@ -1,14 +1,59 @@
 use crate::board::Position;
+use crate::error::ChessError;
+use std::cmp::Ordering;
+use std::fmt;
 use std::ops::Mul;
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Offset {
     pub file: i8,
     pub rank: i8,
 }
 
 impl Offset {
-    pub fn new(file: i8, rank: i8) -> Self {
+    // North points towards black's side of the board (rank 8)
+    pub const NORTH: Offset = Offset::new(0, 1);
+    pub const SOUTH: Offset = Offset::new(0, -1);
+    pub const EAST: Offset = Offset::new(1, 0);
+    pub const WEST: Offset = Offset::new(-1, 0);
+    pub const NORTH_EAST: Offset = Offset::new(1, 1);
+    pub const NORTH_WEST: Offset = Offset::new(-1, 1);
+    pub const SOUTH_EAST: Offset = Offset::new(1, -1);
+    pub const SOUTH_WEST: Offset = Offset::new(-1, -1);
+
+    // Rook directions
+    pub const STRAIGHT_DIRECTIONS: [Offset; 4] =
+        [Offset::EAST, Offset::WEST, Offset::NORTH, Offset::SOUTH];
+    // Bishop directions
+    pub const DIAGONAL_DIRECTIONS: [Offset; 4] = [
+        Offset::NORTH_EAST,
+        Offset::SOUTH_EAST,
+        Offset::NORTH_WEST,
+        Offset::SOUTH_WEST,
+    ];
+    // Queen and king directions
+    pub const DIRECTIONS: [Offset; 8] = [
+        Offset::EAST,
+        Offset::WEST,
+        Offset::NORTH,
+        Offset::SOUTH,
+        Offset::NORTH_EAST,
+        Offset::SOUTH_EAST,
+        Offset::NORTH_WEST,
+        Offset::SOUTH_WEST,
+    ];
+    pub const KNIGHT_JUMPS: [Offset; 8] = [
+        Offset::new(2, 1),
+        Offset::new(2, -1),
+        Offset::new(-2, 1),
+        Offset::new(-2, -1),
+        Offset::new(1, 2),
+        Offset::new(1, -2),
+        Offset::new(-1, 2),
+        Offset::new(-1, -2),
+    ];
+
+    pub const fn new(file: i8, rank: i8) -> Self {
         Self { file, rank }
     }
 }
@@ -43,8 +88,9 @@ impl MoveShape {
         if from == to {
             return Err("From positon can't be same as to position".to_string());
         }
-        let delta_file = (to.file - from.file).abs();
-        let delta_rank = (to.rank - from.rank).abs();
+        let delta = from.offset_to(to);
+        let delta_file = delta.file.abs();
+        let delta_rank = delta.rank.abs();
         let distance = delta_file.max(delta_rank);
 
         if (delta_file == 2 && delta_rank == 1) || (delta_file == 1 && delta_rank == 2) {
@@ -72,7 +118,7 @@ impl MoveShape {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum PieceType {
     Pawn,
     Bishop,
@@ -82,13 +128,52 @@ pub enum PieceType {
     King,
 }
 
+impl PieceType {
+    // Material value in centipawns, the king is never traded so it has none
+    pub fn value(&self) -> i32 {
+        match self {
+            PieceType::Pawn => 100,
+            PieceType::Knight => 320,
+            PieceType::Bishop => 330,
+            PieceType::Rook => 500,
+            PieceType::Queen => 900,
+            PieceType::King => 0,
+        }
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum PieceColor {
     White,
     Black,
 }
 
-#[derive(Copy, Clone, Debug)]
+impl PieceColor {
+    // The step a pawn of this color takes forward
+    pub fn pawn_direction(self) -> Offset {
+        match self {
+            PieceColor::White => Offset::NORTH,
+            PieceColor::Black => Offset::SOUTH,
+        }
+    }
+
+    // Rank the pawns start on, from where they can move two squares
+    pub fn starting_pawn_rank(self) -> i8 {
+        match self {
+            PieceColor::White => 1,
+            PieceColor::Black => 6,
+        }
+    }
+
+    pub fn promotion_rank(self) -> i8 {
+        match self {
+            PieceColor::White => 7,
+            PieceColor::Black => 0,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Piece {
     pub type_: PieceType,
     pub color: PieceColor,
@@ -102,9 +187,8 @@ impl Piece {
             (PieceType::Queen, MoveShape::Straight(_)) => true,
             (PieceType::Queen, MoveShape::Diagonal(_)) => true,
             (PieceType::Knight, MoveShape::Knight) => true,
-            (PieceType::King, MoveShape::Straight(data)) => {
-                data.distance == 1 || data.distance == 2
-            }
+            // Castling is checked separately, any other king move is a single step
+            (PieceType::King, MoveShape::Straight(data)) => data.distance == 1,
             (PieceType::King, MoveShape::Diagonal(data)) => data.distance == 1,
             (PieceType::Pawn, MoveShape::Straight(data)) => match self.color {
                 PieceColor::White => {
@@ -126,15 +210,15 @@ impl Piece {
         let Some(shape) = move_.shape() else {
             return false;
         };
+        self.validate_pawn_shape(move_.from(), shape, is_capture)
+    }
 
+    // Same as validate_pawn_rules for a move from `from` whose shape is already known
+    pub fn validate_pawn_shape(&self, from: Position, shape: MoveShape, is_capture: bool) -> bool {
         match (shape, is_capture) {
             (MoveShape::Straight(data), false) => {
                 if data.distance == 2 {
-                    let starting_rank = match self.color {
-                        PieceColor::White => 1,
-                        PieceColor::Black => 6,
-                    };
-                    move_.from().rank == starting_rank
+                    from.rank == self.color.starting_pawn_rank()
                 } else {
                     data.distance == 1
                 }
@@ -145,15 +229,90 @@ impl Piece {
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Move {
     from: Position,
     to: Position,
+    promotion: Option<PieceType>,
+}
+
+// Sorted by from square, then to square, then promotion piece, with squares in a1, b1, ... h8
+// order. This is the same as comparing square indices.
+impl Ord for Move {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let key = |move_: &Move| {
+            (
+                move_.from.rank,
+                move_.from.file,
+                move_.to.rank,
+                move_.to.file,
+                move_.promotion,
+            )
+        };
+        key(self).cmp(&key(other))
+    }
+}
+
+impl PartialOrd for Move {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
 impl Move {
     pub fn new(from: Position, to: Position) -> Self {
-        Self { from, to }
+        Self {
+            from,
+            to,
+            promotion: None,
+        }
+    }
+
+    pub fn new_promotion(from: Position, to: Position, promotion: PieceType) -> Self {
+        Self {
+            from,
+            to,
+            promotion: Some(promotion),
+        }
+    }
+
+    // Parses moves like e2e4 or e7e8q
+    pub fn from_uci(uci: &str) -> Result<Self, ChessError> {
+        let invalid = || ChessError::InvalidUci(uci.to_string());
+        if !uci.is_ascii() || !(uci.len() == 4 || uci.len() == 5) {
+            return Err(invalid());
+        }
+
+        let from = Position::from_algebraic(&uci[0..2]).map_err(|_| invalid())?;
+        let to = Position::from_algebraic(&uci[2..4]).map_err(|_| invalid())?;
+        if from == to {
+            return Err(invalid());
+        }
+        // Promotions can only go from the seventh rank to the eighth, or the second to the first
+        let promotion_ranks = (from.rank == 6 && to.rank == 7) || (from.rank == 1 && to.rank == 0);
+        if uci.len() == 5 && !promotion_ranks {
+            return Err(invalid());
+        }
+        let promotion = match uci[4..].chars().next() {
+            None => return Ok(Move::new(from, to)),
+            Some('q') => PieceType::Queen,
+            Some('r') => PieceType::Rook,
+            Some('b') => PieceType::Bishop,
+            Some('n') => PieceType::Knight,
+            Some(_) => return Err(invalid()),
+        };
+        Ok(Move::new_promotion(from, to, promotion))
+    }
+
+    // Coordinate notation as pasted from various sources, a UCI move with an optional `-` or
+    // `x` between the squares, e.g. e2-e4 or d4xe5
+    pub fn from_coordinate(text: &str) -> Result<Self, ChessError> {
+        let invalid = || ChessError::InvalidUci(text.to_string());
+        let has_separator = text.is_ascii() && matches!(text.get(2..3), Some("-" | "x"));
+        if !has_separator {
+            return Self::from_uci(text);
+        }
+        Self::from_uci(&format!("{}{}", &text[..2], &text[3..])).map_err(|_| invalid())
     }
 
     pub fn from(&self) -> Position {
@@ -164,6 +323,10 @@ impl Move {
         self.to
     }
 
+    pub fn promotion(&self) -> Option<PieceType> {
+        self.promotion
+    }
+
     pub fn shape(&self) -> Option<MoveShape> {
         MoveShape::from_positions(self.from, self.to).ok()
     }
@@ -172,6 +335,25 @@ impl Move {
         self.from.is_on_board() && self.to.is_on_board()
     }
 
+    // Single step along a line, or the whole jump for knight moves
+    pub fn direction(&self) -> Option<Offset> {
+        let delta = self.from.offset_to(self.to);
+        match self.shape()? {
+            MoveShape::Knight => Some(delta),
+            MoveShape::Straight(_) | MoveShape::Diagonal(_) => {
+                Some(Offset::new(delta.file.signum(), delta.rank.signum()))
+            }
+        }
+    }
+
+    // Number of steps in the direction, a knight jump is one step
+    pub fn distance(&self) -> Option<i8> {
+        match self.shape()? {
+            MoveShape::Knight => Some(1),
+            MoveShape::Straight(data) | MoveShape::Diagonal(data) => Some(data.distance),
+        }
+    }
+
     pub fn path(&self) -> Result<Vec<Position>, String> {
         if self.from == self.to {
             return Ok(Vec::new());
@@ -187,9 +369,7 @@ impl Move {
                 Ok(vec![self.to])
             }
             MoveShape::Straight(_) | MoveShape::Diagonal(_) => {
-                let delta_file = self.to.file - self.from.file;
-                let delta_rank = self.to.rank - self.from.rank;
-                let step = Offset::new(delta_file.signum(), delta_rank.signum());
+                let step = self.direction().expect("Move has a shape");
 
                 let mut positions = Vec::new();
                 let mut current = self.from + step;
@@ -213,3 +393,169 @@ impl Move {
         Ok(path)
     }
 }
+
+// Written like UCI moves, e.g. e2e4 or e7e8q
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.from.to_algebraic(), self.to.to_algebraic())?;
+        if let Some(promotion) = self.promotion {
+            let suffix = match promotion {
+                PieceType::Pawn => 'p',
+                PieceType::Knight => 'n',
+                PieceType::Bishop => 'b',
+                PieceType::Rook => 'r',
+                PieceType::Queen => 'q',
+                PieceType::King => 'k',
+            };
+            write!(f, "{}", suffix)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Move({})", self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::Position;
+    use crate::error::ChessError;
+    use crate::piece::{Move, Offset, PieceColor, PieceType};
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_offset_directions() {
+        assert_eq!(Offset::NORTH, Offset::new(0, 1));
+        assert_eq!(Offset::SOUTH_WEST, Offset::new(-1, -1));
+        assert_eq!(Offset::NORTH * 3, Offset::new(0, 3));
+        assert_eq!(Offset::DIRECTIONS.len(), 8);
+        assert!(
+            Offset::STRAIGHT_DIRECTIONS
+                .iter()
+                .chain(Offset::DIAGONAL_DIRECTIONS.iter())
+                .all(|direction| Offset::DIRECTIONS.contains(direction))
+        );
+    }
+
+    #[test]
+    fn test_pawn_ranks() {
+        assert_eq!(PieceColor::White.pawn_direction(), Offset::new(0, 1));
+        assert_eq!(PieceColor::Black.pawn_direction(), Offset::new(0, -1));
+        assert_eq!(PieceColor::White.starting_pawn_rank(), 1);
+        assert_eq!(PieceColor::Black.starting_pawn_rank(), 6);
+        assert_eq!(PieceColor::White.promotion_rank(), 7);
+        assert_eq!(PieceColor::Black.promotion_rank(), 0);
+
+        // Two pushes from the start reach the fourth and fifth ranks
+        for color in [PieceColor::White, PieceColor::Black] {
+            let double_push = color.starting_pawn_rank() + 2 * color.pawn_direction().rank;
+            assert_eq!(double_push, if color == PieceColor::White { 3 } else { 4 });
+        }
+    }
+
+    #[test]
+    fn test_move_display() {
+        let move_ = Move::new(Position::new(4, 1), Position::new(4, 3));
+        assert_eq!(move_.to_string(), "e2e4");
+        assert_eq!(format!("{:?}", move_), "Move(e2e4)");
+
+        let promotion =
+            Move::new_promotion(Position::new(0, 6), Position::new(1, 7), PieceType::Knight);
+        assert_eq!(promotion.to_string(), "a7b8n");
+
+        // Squares off the board don't panic
+        let off_board = Move::new(Position::new(-1, 0), Position::new(0, 0));
+        assert_eq!(off_board.to_string(), "??a1");
+    }
+
+    #[test]
+    fn test_move_from_uci() {
+        let move_ = Move::from_uci("e2e4").unwrap();
+        assert_eq!(move_, Move::new(Position::new(4, 1), Position::new(4, 3)));
+
+        let promotion = Move::from_uci("a7b8n").unwrap();
+        assert_eq!(promotion.promotion(), Some(PieceType::Knight));
+        assert_eq!(promotion.to_string(), "a7b8n");
+
+        assert!(Move::from_uci("e2").is_err());
+        assert!(Move::from_uci("e2e9").is_err());
+        assert!(Move::from_uci("e7e8x").is_err());
+        assert!(Move::from_uci("e7e8k").is_err());
+        assert!(Move::from_uci("e8e8q").is_err());
+        assert!(Move::from_uci("e2e4q").is_err());
+        assert!(Move::from_uci("e4e4").is_err());
+        assert!(Move::from_uci("b2a1r").is_ok());
+        assert!(Move::from_uci("e2e4e5").is_err());
+    }
+
+    #[test]
+    fn test_move_from_coordinate() {
+        let move_ = Move::from_uci("e2e4").unwrap();
+        for text in ["e2e4", "e2-e4", "e2xe4"] {
+            assert_eq!(Move::from_coordinate(text), Ok(move_));
+        }
+        assert_eq!(Move::from_coordinate("a7xb8n"), Move::from_uci("a7b8n"));
+
+        assert_eq!(
+            Move::from_coordinate("e2--e4"),
+            Err(ChessError::InvalidUci("e2--e4".to_string()))
+        );
+        assert!(Move::from_coordinate("e2+e4").is_err());
+        assert!(Move::from_coordinate("e-2e4").is_err());
+        // Plain UCI parsing stays strict
+        assert!(Move::from_uci("e2-e4").is_err());
+    }
+
+    #[test]
+    fn test_move_direction() {
+        let move_ = Move::from_uci("a1h8").unwrap();
+        assert_eq!(move_.direction(), Some(Offset::NORTH_EAST));
+        assert_eq!(move_.distance(), Some(7));
+
+        let move_ = Move::from_uci("e4e2").unwrap();
+        assert_eq!(move_.direction(), Some(Offset::SOUTH));
+        assert_eq!(move_.distance(), Some(2));
+
+        let move_ = Move::from_uci("g1f3").unwrap();
+        assert_eq!(move_.direction(), Some(Offset::new(-1, 2)));
+        assert_eq!(move_.distance(), Some(1));
+
+        // Not along a line or a knight jump
+        let move_ = Move::from_uci("a1c4").unwrap();
+        assert_eq!(move_.direction(), None);
+        assert_eq!(move_.distance(), None);
+    }
+
+    #[test]
+    fn test_move_equality() {
+        let move_ = Move::new(Position::new(4, 1), Position::new(4, 3));
+        assert_eq!(move_, Move::new(Position::new(4, 1), Position::new(4, 3)));
+        assert_ne!(move_, Move::new(Position::new(4, 1), Position::new(4, 2)));
+
+        // Promotions to different pieces are different moves
+        let queen = Move::new_promotion(Position::new(0, 6), Position::new(0, 7), PieceType::Queen);
+        let rook = Move::new_promotion(Position::new(0, 6), Position::new(0, 7), PieceType::Rook);
+        assert_ne!(queen, rook);
+        assert_ne!(queen, Move::new(Position::new(0, 6), Position::new(0, 7)));
+
+        let moves: HashSet<Move> = [move_, move_, queen].into_iter().collect();
+        assert_eq!(moves.len(), 2);
+    }
+
+    #[test]
+    fn test_move_ordering() {
+        let mut moves: Vec<Move> = ["g1f3", "b1c3", "e2e4", "e2e3", "a7a8q", "a7a8n", "a7a8"]
+            .into_iter()
+            .map(|uci| Move::from_uci(uci).unwrap())
+            .collect();
+        moves.sort();
+        let sorted: Vec<String> = moves.iter().map(|move_| move_.to_string()).collect();
+        assert_eq!(
+            sorted,
+            vec!["b1c3", "g1f3", "e2e3", "e2e4", "a7a8", "a7a8n", "a7a8q"]
+        );
+    }
+}
@@ -0,0 +1,371 @@
+use crate::board::{Board, CastlingSide, Position};
+use crate::error::ChessError;
+use crate::piece::{Move, PieceType};
+
+fn piece_from_letter(letter: char) -> Option<PieceType> {
+    match letter {
+        'N' => Some(PieceType::Knight),
+        'B' => Some(PieceType::Bishop),
+        'R' => Some(PieceType::Rook),
+        'Q' => Some(PieceType::Queen),
+        'K' => Some(PieceType::King),
+        _ => None,
+    }
+}
+
+fn piece_letter(piece_type: PieceType) -> char {
+    match piece_type {
+        PieceType::Pawn => 'P',
+        PieceType::Knight => 'N',
+        PieceType::Bishop => 'B',
+        PieceType::Rook => 'R',
+        PieceType::Queen => 'Q',
+        PieceType::King => 'K',
+    }
+}
+
+impl Board {
+    // Writes a legal move in standard algebraic notation. The check and mate suffixes come
+    // from the position after the move, so discovered checks are marked too.
+    pub fn move_to_san(&self, move_: Move) -> Result<String, ChessError> {
+        let mut after = self.position_copy();
+        after.make_move_checked(move_)?;
+
+        let mut san = match self.is_castle(move_) {
+            Some(CastlingSide::Kingside) => "O-O".to_string(),
+            Some(CastlingSide::Queenside) => "O-O-O".to_string(),
+            None => self.san_without_suffix(move_),
+        };
+        if after.is_checkmate() {
+            san.push('#');
+        } else if after.is_in_check() {
+            san.push('+');
+        }
+        Ok(san)
+    }
+
+    fn san_without_suffix(&self, move_: Move) -> String {
+        let (from, to) = (move_.from(), move_.to());
+        let piece_type = self
+            .piece_at_pos(from)
+            .expect("Legal move starts on a piece")
+            .type_;
+        // A pawn changing file on a legal move always captures, also en passant
+        let is_capture =
+            self.is_occupied(to) || (piece_type == PieceType::Pawn && from.file != to.file);
+
+        let mut san = String::new();
+        if piece_type == PieceType::Pawn {
+            if is_capture {
+                san.push(from.file_char());
+            }
+        } else {
+            san.push(piece_letter(piece_type));
+
+            // Other pieces of the same kind that could also go to the same square
+            let others: Vec<Position> = self
+                .legal_moves_iter()
+                .filter(|other| {
+                    other.to() == to
+                        && other.from() != from
+                        && self
+                            .piece_at_pos(other.from())
+                            .is_some_and(|piece| piece.type_ == piece_type)
+                        && self.is_castle(*other).is_none()
+                })
+                .map(|other| other.from())
+                .collect();
+            if !others.is_empty() {
+                if others.iter().all(|other| other.file != from.file) {
+                    san.push(from.file_char());
+                } else if others.iter().all(|other| other.rank != from.rank) {
+                    san.push(from.rank_char());
+                } else {
+                    san.push_str(&from.to_algebraic());
+                }
+            }
+        }
+
+        if is_capture {
+            san.push('x');
+        }
+        san.push_str(&to.to_algebraic());
+        if let Some(promotion) = move_.promotion() {
+            san.push('=');
+            san.push(piece_letter(promotion));
+        }
+        san
+    }
+
+    // Parses standard algebraic notation like `Nbd7`, `exd5`, `e8=Q+` or `O-O` into a legal move
+    pub fn san_to_move(&self, san: &str) -> Result<Move, ChessError> {
+        let invalid = || ChessError::InvalidSan(san.to_string());
+        let trimmed = san.trim_end_matches(['+', '#', '!', '?']);
+
+        if matches!(trimmed, "O-O" | "0-0" | "O-O-O" | "0-0-0") {
+            return self
+                .castling_from_san(trimmed.len() == 3)
+                .ok_or_else(invalid);
+        }
+
+        let mut chars: Vec<char> = trimmed.chars().collect();
+
+        // Promotion suffix, written as `e8=Q` or `e8Q`
+        let mut promotion = None;
+        if let Some(&last) = chars.last()
+            && last.is_ascii_uppercase()
+            && chars.len() > 2
+        {
+            promotion = Some(piece_from_letter(last).ok_or_else(invalid)?);
+            chars.pop();
+            if chars.last() == Some(&'=') {
+                chars.pop();
+            }
+        }
+
+        let piece_type = match chars.first().copied().and_then(piece_from_letter) {
+            Some(piece_type) => {
+                chars.remove(0);
+                piece_type
+            }
+            None => PieceType::Pawn,
+        };
+
+        if chars.len() < 2 {
+            return Err(invalid());
+        }
+        let to_str: String = chars.split_off(chars.len() - 2).into_iter().collect();
+        let to = Position::from_algebraic(&to_str).map_err(|_| invalid())?;
+        if chars.last() == Some(&'x') {
+            chars.pop();
+        }
+
+        // What is left is the disambiguation, a file, a rank, or both
+        let mut from_file = None;
+        let mut from_rank = None;
+        for ch in chars {
+            match ch {
+                'a'..='h' if from_file.is_none() => from_file = Some(ch as i8 - 'a' as i8),
+                '1'..='8' if from_rank.is_none() => from_rank = Some(ch as i8 - '1' as i8),
+                _ => return Err(invalid()),
+            }
+        }
+
+        // Pawns reaching the last rank must name a queen, rook, bishop or knight, and nothing else may
+        let reaches_last_rank = to.rank == 0 || to.rank == 7;
+        match (piece_type, promotion) {
+            (PieceType::Pawn, None) if reaches_last_rank => return Err(invalid()),
+            (PieceType::Pawn, Some(PieceType::King)) => return Err(invalid()),
+            (PieceType::Pawn, Some(_)) if !reaches_last_rank => return Err(invalid()),
+            (PieceType::Pawn, _) => {}
+            (_, Some(_)) => return Err(invalid()),
+            (_, None) => {}
+        }
+
+        let mut candidates = self.legal_moves_iter().filter(|move_| {
+            move_.to() == to
+                && self
+                    .piece_at_pos(move_.from())
+                    .is_some_and(|piece| piece.type_ == piece_type)
+                && from_file.is_none_or(|file| move_.from().file == file)
+                && from_rank.is_none_or(|rank| move_.from().rank == rank)
+                && move_.promotion() == promotion
+                && self.is_castle(*move_).is_none()
+        });
+        let (Some(move_), None) = (candidates.next(), candidates.next()) else {
+            return Err(invalid());
+        };
+        Ok(move_)
+    }
+
+    // Plays space separated SAN moves like `e4 e5 Nf3`, the board is left unchanged on errors
+    pub fn apply_san_line(&mut self, line: &str) -> Result<(), ChessError> {
+        let mut board = self.clone();
+        board.play_san_line(line, |_, _| {})?;
+        *self = board;
+        Ok(())
+    }
+
+    // Plays the SAN moves of line on this board, calling on_move with the board before each
+    // move. Moves before an invalid one stay played.
+    pub(crate) fn play_san_line(
+        &mut self,
+        line: &str,
+        mut on_move: impl FnMut(&Board, Move),
+    ) -> Result<(), ChessError> {
+        for (index, san) in line.split_whitespace().enumerate() {
+            let invalid = || ChessError::InvalidSanInLine(index, san.to_string());
+            let move_ = self.san_to_move(san).map_err(|_| invalid())?;
+            on_move(self, move_);
+            self.make_move_checked(move_).map_err(|_| invalid())?;
+        }
+        Ok(())
+    }
+
+    fn castling_from_san(&self, kingside: bool) -> Option<Move> {
+        let side = if kingside {
+            CastlingSide::Kingside
+        } else {
+            CastlingSide::Queenside
+        };
+        self.legal_moves_iter()
+            .find(|&move_| self.is_castle(move_) == Some(side))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::{Board, Position};
+    use crate::error::ChessError;
+    use crate::piece::{Move, PieceType};
+
+    #[test]
+    fn test_san_to_move() {
+        let board = Board::starting_position();
+        assert_eq!(
+            board.san_to_move("e4"),
+            Ok(Move::new(Position::new(4, 1), Position::new(4, 3)))
+        );
+        assert_eq!(
+            board.san_to_move("Nf3"),
+            Ok(Move::new(Position::new(6, 0), Position::new(5, 2)))
+        );
+        assert!(board.san_to_move("e5").is_err());
+        assert!(board.san_to_move("Nd4").is_err());
+        assert!(board.san_to_move("Zf3").is_err());
+        assert!(board.san_to_move("").is_err());
+
+        // Both knights can reach d2, so the file is needed
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1").unwrap();
+        assert!(board.san_to_move("Nd2").is_err());
+        assert_eq!(
+            board.san_to_move("Nbd2"),
+            Ok(Move::new(Position::new(1, 0), Position::new(3, 1)))
+        );
+        assert_eq!(
+            board.san_to_move("Nfd2+"),
+            Ok(Move::new(Position::new(5, 0), Position::new(3, 1)))
+        );
+
+        let board = Board::from_fen("r3k2r/8/8/3p4/4P3/8/8/R3K2R w KQkq - 0 1").unwrap();
+        assert_eq!(
+            board.san_to_move("exd5"),
+            Ok(Move::new(Position::new(4, 3), Position::new(3, 4)))
+        );
+        assert_eq!(
+            board.san_to_move("O-O"),
+            Ok(Move::new(Position::new(4, 0), Position::new(6, 0)))
+        );
+        assert_eq!(
+            board.san_to_move("O-O-O"),
+            Ok(Move::new(Position::new(4, 0), Position::new(2, 0)))
+        );
+        // The king's two square step is only written as castling
+        assert!(board.san_to_move("Kg1").is_err());
+    }
+
+    #[test]
+    fn test_move_to_san() {
+        let san = |board: &Board, uci: &str| board.move_to_san(Move::from_uci(uci).unwrap());
+
+        let board = Board::starting_position();
+        assert_eq!(san(&board, "e2e4"), Ok("e4".to_string()));
+        assert_eq!(san(&board, "g1f3"), Ok("Nf3".to_string()));
+        assert!(matches!(
+            san(&board, "e2e5"),
+            Err(ChessError::IllegalMove(_))
+        ));
+
+        // The knight doesn't attack the king itself, moving it uncovers the rook
+        let board = Board::from_fen("4k3/8/8/8/4N3/8/8/4R1K1 w - - 0 1").unwrap();
+        assert_eq!(san(&board, "e4c3"), Ok("Nc3+".to_string()));
+        assert_eq!(san(&board, "e4f6"), Ok("Nf6+".to_string()));
+        assert_eq!(san(&board, "g1g2"), Ok("Kg2".to_string()));
+
+        let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        assert_eq!(san(&board, "a1a8"), Ok("Ra8#".to_string()));
+
+        // Disambiguation by file, by rank, and by both
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1").unwrap();
+        assert_eq!(san(&board, "b1d2"), Ok("Nbd2".to_string()));
+        let board = Board::from_fen("4k3/8/8/R7/8/8/8/R3K3 w - - 0 1").unwrap();
+        assert_eq!(san(&board, "a1a3"), Ok("R1a3".to_string()));
+        let board = Board::from_fen("4k3/8/8/8/Q2Q4/8/8/Q3K3 w - - 0 1").unwrap();
+        assert_eq!(san(&board, "a4d1"), Ok("Qa4d1".to_string()));
+        assert_eq!(san(&board, "d4d8"), Ok("Qd8+".to_string()));
+
+        let board = Board::from_fen("r3k3/1P6/8/3pP3/8/8/8/4K2R w Kq d6 0 1").unwrap();
+        assert_eq!(san(&board, "e5d6"), Ok("exd6".to_string()));
+        assert_eq!(san(&board, "b7a8q"), Ok("bxa8=Q+".to_string()));
+        assert_eq!(san(&board, "b7b8n"), Ok("b8=N".to_string()));
+        assert_eq!(san(&board, "e1g1"), Ok("O-O".to_string()));
+        assert!(matches!(
+            san(&board, "b7b8"),
+            Err(ChessError::PromotionRequired(_))
+        ));
+
+        // Every move reads back as itself
+        let board =
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        for move_ in board.all_legal_moves() {
+            assert_eq!(
+                board.san_to_move(&board.move_to_san(move_).unwrap()),
+                Ok(move_)
+            );
+        }
+    }
+
+    #[test]
+    fn test_apply_san_line() {
+        let mut board = Board::starting_position();
+        board.apply_san_line("e4 e5 Nf3 Nc6 Bb5 a6 O-O").unwrap();
+        let expected =
+            Board::from_fen("r1bqkbnr/1ppp1ppp/p1n5/1B2p3/4P3/5N2/PPPP1PPP/RNBQ1RK1 b kq - 1 4")
+                .unwrap();
+        assert_eq!(board.zobrist_hash(), expected.zobrist_hash());
+        assert_eq!(board.fullmove_number(), 4);
+
+        // Nothing is applied when a move fails
+        let mut board = Board::starting_position();
+        assert_eq!(
+            board.apply_san_line("e4 e5 Ke3"),
+            Err(ChessError::InvalidSanInLine(2, "Ke3".to_string()))
+        );
+        assert_eq!(
+            board.zobrist_hash(),
+            Board::starting_position().zobrist_hash()
+        );
+        assert!(board.apply_san_line("").is_ok());
+    }
+
+    #[test]
+    fn test_san_promotion() {
+        let board = Board::from_fen("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let promotion = |piece_type| {
+            Ok(Move::new_promotion(
+                Position::new(0, 6),
+                Position::new(0, 7),
+                piece_type,
+            ))
+        };
+        assert_eq!(board.san_to_move("a8=Q"), promotion(PieceType::Queen));
+        assert_eq!(board.san_to_move("a8N"), promotion(PieceType::Knight));
+        assert_eq!(
+            board.san_to_move("axb8=R+"),
+            Ok(Move::new_promotion(
+                Position::new(0, 6),
+                Position::new(1, 7),
+                PieceType::Rook
+            ))
+        );
+
+        // Missing, king, or misplaced promotion pieces are rejected
+        let invalid = |san: &str| Err(ChessError::InvalidSan(san.to_string()));
+        assert_eq!(board.san_to_move("a8"), invalid("a8"));
+        assert_eq!(board.san_to_move("a8=K"), invalid("a8=K"));
+        assert_eq!(board.san_to_move("Ke2=Q"), invalid("Ke2=Q"));
+        let board = Board::starting_position();
+        assert_eq!(board.san_to_move("e4=Q"), invalid("e4=Q"));
+    }
+}
@@ -0,0 +1,356 @@
+use crate::board::Board;
+use crate::eval::MATE_SCORE;
+use crate::piece::Move;
+
+const DEFAULT_TABLE_SIZE: usize = 1 << 16;
+
+// Scores this close to MATE_SCORE are mates, and are stored relative to the node
+const MATE_THRESHOLD: i32 = MATE_SCORE - 1000;
+
+#[derive(Clone, Copy)]
+enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+#[derive(Clone, Copy)]
+struct TableEntry {
+    hash: u64,
+    depth: u8,
+    score: i32,
+    bound: Bound,
+    best_move: Option<Move>,
+}
+
+pub struct TranspositionTable {
+    entries: Vec<Option<TableEntry>>,
+}
+
+impl TranspositionTable {
+    pub fn new(size: usize) -> Self {
+        Self {
+            entries: vec![None; size.max(1)],
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.fill(None);
+    }
+
+    fn index(&self, hash: u64) -> usize {
+        (hash % self.entries.len() as u64) as usize
+    }
+
+    fn probe(&self, hash: u64) -> Option<TableEntry> {
+        self.entries[self.index(hash)].filter(|entry| entry.hash == hash)
+    }
+
+    fn store(&mut self, entry: TableEntry) {
+        let index = self.index(entry.hash);
+        // Keep deeper results for the same position
+        if let Some(existing) = self.entries[index]
+            && existing.hash == entry.hash
+            && existing.depth > entry.depth
+        {
+            return;
+        }
+        self.entries[index] = Some(entry);
+    }
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        Self::new(DEFAULT_TABLE_SIZE)
+    }
+}
+
+fn score_to_table(score: i32, ply: i32) -> i32 {
+    if score > MATE_THRESHOLD {
+        score + ply
+    } else if score < -MATE_THRESHOLD {
+        score - ply
+    } else {
+        score
+    }
+}
+
+fn score_from_table(score: i32, ply: i32) -> i32 {
+    if score > MATE_THRESHOLD {
+        score - ply
+    } else if score < -MATE_THRESHOLD {
+        score + ply
+    } else {
+        score
+    }
+}
+
+fn move_to_front(moves: &mut [Move], first_move: Move) {
+    if let Some(index) = moves.iter().position(|&move_| move_ == first_move) {
+        moves.swap(0, index);
+    }
+}
+
+struct Searcher<'a> {
+    nodes: u64,
+    max_nodes: u64,
+    table: &'a mut TranspositionTable,
+}
+
+impl<'a> Searcher<'a> {
+    fn new(max_nodes: u64, table: &'a mut TranspositionTable) -> Self {
+        Self {
+            nodes: 0,
+            max_nodes,
+            table,
+        }
+    }
+
+    // Returns None once the node budget is used up
+    fn negamax(
+        &mut self,
+        board: &mut Board,
+        depth: u8,
+        ply: i32,
+        mut alpha: i32,
+        beta: i32,
+    ) -> Option<i32> {
+        if self.nodes >= self.max_nodes {
+            return None;
+        }
+        self.nodes += 1;
+
+        let hash = board.zobrist_hash();
+        let table_entry = self.table.probe(hash);
+        if let Some(entry) = table_entry
+            && entry.depth >= depth
+        {
+            let score = score_from_table(entry.score, ply);
+            match entry.bound {
+                Bound::Exact => return Some(score),
+                Bound::Lower if score >= beta => return Some(score),
+                Bound::Upper if score <= alpha => return Some(score),
+                _ => {}
+            }
+        }
+
+        let mut legal_moves = board.legal_moves_in_place();
+        if legal_moves.is_empty() {
+            // Mates closer to the root score higher so the quickest mate is preferred
+            return Some(if board.is_in_check() {
+                -MATE_SCORE + ply
+            } else {
+                0
+            });
+        }
+        if depth == 0 {
+            return Some(board.evaluate());
+        }
+
+        board.order_moves(&mut legal_moves);
+        if let Some(table_move) = table_entry.and_then(|entry| entry.best_move) {
+            move_to_front(&mut legal_moves, table_move);
+        }
+
+        let original_alpha = alpha;
+        let mut best_score = -MATE_SCORE - 1;
+        let mut best_move = None;
+        for move_ in legal_moves {
+            board.play_move(move_).expect("Move was legal");
+            let score = self.negamax(board, depth - 1, ply + 1, -beta, -alpha);
+            board.unmake_move();
+            let score = -score?;
+            if score > best_score {
+                best_score = score;
+                best_move = Some(move_);
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        let bound = if best_score <= original_alpha {
+            Bound::Upper
+        } else if best_score >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.table.store(TableEntry {
+            hash,
+            depth,
+            score: score_to_table(best_score, ply),
+            bound,
+            best_move,
+        });
+        Some(best_score)
+    }
+
+    // Returns the best move, its score, and whether the whole depth was searched
+    fn search_root(
+        &mut self,
+        board: &mut Board,
+        depth: u8,
+        first_move: Option<Move>,
+    ) -> (Option<Move>, i32, bool) {
+        let mut legal_moves = board.legal_moves_in_place();
+        if legal_moves.is_empty() {
+            let score = if board.is_in_check() { -MATE_SCORE } else { 0 };
+            return (None, score, true);
+        }
+
+        // Searching the previous best move first gives more cutoffs
+        board.order_moves(&mut legal_moves);
+        if let Some(first_move) = first_move {
+            move_to_front(&mut legal_moves, first_move);
+        }
+
+        let mut alpha = -MATE_SCORE - 1;
+        let beta = MATE_SCORE + 1;
+        let mut best_move = None;
+
+        for move_ in legal_moves {
+            board.play_move(move_).expect("Move was legal");
+            let score = self.negamax(board, depth - 1, 1, -beta, -alpha);
+            board.unmake_move();
+            let Some(score) = score else {
+                return (best_move, alpha, false);
+            };
+            let score = -score;
+            if best_move.is_none() || score > alpha {
+                alpha = score;
+                best_move = Some(move_);
+            }
+        }
+        (best_move, alpha, true)
+    }
+}
+
+impl Board {
+    pub fn best_move(&self, depth: u8) -> Option<Move> {
+        let mut table = TranspositionTable::default();
+        let (best_move, _, _) = Searcher::new(u64::MAX, &mut table).search_root(
+            &mut self.position_copy(),
+            depth.max(1),
+            None,
+        );
+        best_move
+    }
+
+    pub fn search(&self, max_depth: u8, max_nodes: u64) -> (Option<Move>, i32) {
+        self.search_with_table(max_depth, max_nodes, &mut TranspositionTable::default())
+    }
+
+    // Same as search, but with a table that can be kept between moves
+    pub fn search_with_table(
+        &self,
+        max_depth: u8,
+        max_nodes: u64,
+        table: &mut TranspositionTable,
+    ) -> (Option<Move>, i32) {
+        let mut searcher = Searcher::new(max_nodes, table);
+        let mut board = self.position_copy();
+        let mut result = (None, 0);
+
+        for depth in 1..=max_depth.max(1) {
+            let (best_move, score, completed) = searcher.search_root(&mut board, depth, result.0);
+            // A partial iteration is only used if no earlier iteration finished
+            if completed || (result.0.is_none() && best_move.is_some()) {
+                result = (best_move, score);
+            }
+            if !completed {
+                break;
+            }
+        }
+
+        // The budget ran out before any root move was searched, so play the move that would
+        // have been searched first rather than reporting no move
+        if result.0.is_none() {
+            let mut legal_moves = self.all_legal_moves();
+            self.order_moves(&mut legal_moves);
+            if let Some(&first_move) = legal_moves.first() {
+                result = (Some(first_move), self.evaluate());
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::{Board, Position};
+    use crate::eval::MATE_SCORE;
+    use crate::search::TranspositionTable;
+
+    #[test]
+    fn test_best_move() {
+        // Back rank mate with Ra8
+        let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let move_ = board.best_move(2).unwrap();
+        assert_eq!(move_.to(), Position::new(0, 7));
+
+        // Black queen on d4 is hanging to the rook
+        let board = Board::from_fen("4k3/8/8/8/3q4/8/8/3RK3 w - - 0 1").unwrap();
+        let move_ = board.best_move(1).unwrap();
+        assert_eq!(move_.to(), Position::new(3, 3));
+
+        // A deeper search still plays the immediate mate instead of a slower one
+        let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5KR w - - 0 1").unwrap();
+        let move_ = board.best_move(3).unwrap();
+        assert_eq!(move_.to(), Position::new(0, 7));
+
+        // No move once the game is over
+        let board = Board::from_fen("R5k1/8/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert!(board.best_move(2).is_none());
+    }
+
+    #[test]
+    fn test_search() {
+        let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let (move_, score) = board.search(3, u64::MAX);
+        assert_eq!(move_.unwrap().to(), Position::new(0, 7));
+        assert_eq!(score, MATE_SCORE - 1);
+
+        // A tiny node budget still gives back a legal move
+        let board = Board::starting_position();
+        let (move_, _) = board.search(10, 50);
+        assert!(board.move_legal(move_.unwrap()));
+
+        // Even without any nodes to spend there is a move while legal moves exist
+        let (move_, _) = board.search(3, 0);
+        assert!(board.move_legal(move_.unwrap()));
+
+        let board = Board::from_fen("1k6/1P6/1K6/8/8/8/8/8 b - - 0 1").unwrap();
+        let (move_, score) = board.search(3, 0);
+        assert!(move_.is_none());
+        assert_eq!(score, 0);
+        let (move_, score) = board.search(3, 1000);
+        assert!(move_.is_none());
+        assert_eq!(score, 0);
+    }
+
+    #[test]
+    fn test_transposition_table() {
+        let mut table = TranspositionTable::new(1024);
+        assert_eq!(table.size(), 1024);
+
+        let board = Board::from_fen("4k3/8/8/8/3q4/8/8/3RK3 w - - 0 1").unwrap();
+        let (move_, _) = board.search_with_table(3, u64::MAX, &mut table);
+        assert_eq!(move_.unwrap().to(), Position::new(3, 3));
+        assert!(table.entries.iter().any(|entry| entry.is_some()));
+
+        // The table is reused for the next search, and mate scores survive the round trip
+        let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let (move_, score) = board.search_with_table(3, u64::MAX, &mut table);
+        assert_eq!(move_.unwrap().to(), Position::new(0, 7));
+        assert_eq!(score, MATE_SCORE - 1);
+
+        table.clear();
+        assert!(table.entries.iter().all(|entry| entry.is_none()));
+    }
+}
@@ -0,0 +1,61 @@
+use crate::board::{BOARD_HEIGHT, BOARD_WIDTH};
+use crate::piece::{Piece, PieceColor, PieceType};
+
+const SQUARE_COUNT: usize = (BOARD_WIDTH * BOARD_HEIGHT) as usize;
+const PIECE_KEYS: usize = 12 * SQUARE_COUNT;
+const SIDE_KEY: usize = PIECE_KEYS;
+const CASTLING_KEYS: usize = SIDE_KEY + 1;
+const EN_PASSANT_KEYS: usize = CASTLING_KEYS + 4;
+const KEY_COUNT: usize = EN_PASSANT_KEYS + BOARD_WIDTH as usize;
+
+// Fixed pseudo-random keys generated with splitmix64, so hashes are stable between runs
+const KEYS: [u64; KEY_COUNT] = generate_keys();
+
+const fn generate_keys() -> [u64; KEY_COUNT] {
+    let mut keys = [0; KEY_COUNT];
+    let mut state: u64 = 0x2545_F491_4F6C_DD1D;
+    let mut i = 0;
+    while i < KEY_COUNT {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        keys[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    keys
+}
+
+pub fn piece_key(piece: Piece, square_index: usize) -> u64 {
+    let type_index = match piece.type_ {
+        PieceType::Pawn => 0,
+        PieceType::Knight => 1,
+        PieceType::Bishop => 2,
+        PieceType::Rook => 3,
+        PieceType::Queen => 4,
+        PieceType::King => 5,
+    };
+    let color_index = match piece.color {
+        PieceColor::White => 0,
+        PieceColor::Black => 6,
+    };
+    KEYS[(type_index + color_index) * SQUARE_COUNT + square_index]
+}
+
+pub fn side_key() -> u64 {
+    KEYS[SIDE_KEY]
+}
+
+pub fn castling_key(color: PieceColor, kingside: bool) -> u64 {
+    let index = match (color, kingside) {
+        (PieceColor::White, true) => 0,
+        (PieceColor::White, false) => 1,
+        (PieceColor::Black, true) => 2,
+        (PieceColor::Black, false) => 3,
+    };
+    KEYS[CASTLING_KEYS + index]
+}
+
+pub fn en_passant_key(file: i8) -> u64 {
+    KEYS[EN_PASSANT_KEYS + file as usize]
+}